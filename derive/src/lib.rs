@@ -0,0 +1,67 @@
+#![allow(non_snake_case)]
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, LitInt, LitStr};
+
+/// Implements `ABC_Save_Files::SaveComponent`.
+///
+/// Configured with `#[save_component(...)]`:
+/// - `key = "..."` the key the component is stored under, the type's name by default
+/// - `version = N` the component's version, `0` by default
+/// - `default` loading a save without this component returns `Default::default()`
+#[proc_macro_derive(SaveComponent, attributes(save_component))]
+pub fn derive_save_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut key = input.ident.to_string();
+    let mut version = 0u32;
+    let mut default = false;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("save_component"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                key = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("version") {
+                version = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+            } else if meta.path.is_ident("default") {
+                default = true;
+            } else {
+                return Err(meta.error("expected `key`, `version` or `default`"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let default_component = default.then(|| {
+        quote! {
+            fn default_component() -> ::std::option::Option<Self> {
+                ::std::option::Option::Some(<Self as ::std::default::Default>::default())
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::ABC_Save_Files::SaveComponent for #name #ty_generics #where_clause {
+            const KEY: &'static str = #key;
+            const VERSION: u32 = #version;
+
+            #default_component
+        }
+    })
+}
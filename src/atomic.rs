@@ -0,0 +1,74 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::backup;
+
+/// An encoded save waiting to be written, so the encoding and the slow disk access can
+/// happen at different times or on different threads.
+pub(crate) struct PendingWrite {
+    pub(crate) path: PathBuf,
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) backups: usize,
+}
+
+impl PendingWrite {
+    pub(crate) fn write(&self) -> io::Result<()> {
+        // if the folder doesn't exist create it
+        if let Some(folder) = self.path.parent() {
+            fs::create_dir_all(folder)?;
+        }
+
+        backup::rotate(&self.path, self.backups)?;
+        write_atomic(&self.path, &self.bytes)
+    }
+}
+
+/// The sibling file a save is written to before it replaces `path`.
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(".tmp");
+
+    path.with_file_name(file_name)
+}
+
+/// Writes `bytes` to `path` so that the file is either fully replaced or left untouched,
+/// even if the process dies or the machine loses power halfway through.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let temp = temp_path(path);
+
+    let result = write_and_sync(&temp, bytes).and_then(|_| fs::rename(&temp, path));
+
+    if result.is_err() {
+        // don't leave a half written temp file behind, the original is still intact
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+
+    sync_parent_dir(path)
+}
+
+fn write_and_sync(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+// the rename itself is only durable once the directory entry is flushed
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => File::open(parent)?.sync_all(),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{Format, Json, SaveFile, SaveFileError};
+
+/// The outcome of the most recent save written by an [`AutoSaver`].
+pub type SaveResult = Result<(), Arc<SaveFileError>>;
+
+/// Saves a shared [`SaveFile`] on a background thread so the game thread never waits on disk.
+///
+/// Calls to [`request_save`](AutoSaver::request_save) are debounced: the save is written
+/// once no new request has come in for the debounce duration. Pending saves are flushed
+/// when the saver is shut down or dropped.
+pub struct AutoSaver<F: Format = Json> {
+    shared: Arc<Shared<F>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+struct Shared<F: Format> {
+    save_file: Mutex<SaveFile<F>>,
+    state: Mutex<State>,
+    changed: Condvar,
+    path: PathBuf,
+    debounce: Duration,
+}
+
+#[derive(Default)]
+struct State {
+    // when the newest unwritten request came in
+    pending_since: Option<Instant>,
+    // skip the debounce for the pending request
+    immediate: bool,
+    shutdown: bool,
+    requested: u64,
+    completed: u64,
+    last_result: Option<SaveResult>,
+}
+
+impl<F: Format + 'static> AutoSaver<F> {
+    /// Starts a worker that writes `save_file` to `path`, relative to its save directory.
+    pub fn new(save_file: SaveFile<F>, path: impl Into<PathBuf>, debounce: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            save_file: Mutex::new(save_file),
+            state: Mutex::new(State::default()),
+            changed: Condvar::new(),
+            path: path.into(),
+            debounce,
+        });
+
+        let worker = {
+            let shared = shared.clone();
+            thread::spawn(move || shared.run())
+        };
+
+        AutoSaver {
+            shared,
+            worker: Some(worker),
+        }
+    }
+}
+
+impl<F: Format> AutoSaver<F> {
+    /// Locks the shared save file to read or modify it.
+    pub fn save_file(&self) -> MutexGuard<'_, SaveFile<F>> {
+        self.shared
+            .save_file
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Asks for the save file to be written once requests stop coming in for the debounce
+    /// duration.
+    pub fn request_save(&self) {
+        self.shared.request(false);
+    }
+
+    /// Writes the save file now and waits for the write to finish.
+    pub fn flush(&self) -> SaveResult {
+        let ticket = self.shared.request(true);
+
+        let mut state = self.shared.state();
+        while state.completed < ticket {
+            state = self
+                .shared
+                .changed
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        state.last_result.clone().unwrap_or(Ok(()))
+    }
+
+    /// The result of the most recent save, `None` if nothing has been written yet.
+    pub fn last_result(&self) -> Option<SaveResult> {
+        self.shared.state().last_result.clone()
+    }
+
+    /// Writes any pending save and stops the worker.
+    pub fn shutdown(mut self) -> Option<SaveResult> {
+        self.stop();
+
+        self.last_result()
+    }
+
+    fn stop(&mut self) {
+        let Some(worker) = self.worker.take() else {
+            return;
+        };
+
+        self.shared.state().shutdown = true;
+        self.shared.changed.notify_all();
+
+        let _ = worker.join();
+    }
+}
+
+impl<F: Format> Drop for AutoSaver<F> {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl<F: Format> Shared<F> {
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn request(&self, immediate: bool) -> u64 {
+        let mut state = self.state();
+
+        state.requested += 1;
+        state.pending_since = Some(Instant::now());
+        state.immediate |= immediate;
+
+        self.changed.notify_all();
+
+        state.requested
+    }
+
+    fn run(&self) {
+        loop {
+            let Some(ticket) = self.wait_until_due() else {
+                return;
+            };
+
+            let result = self.save().map_err(Arc::new);
+
+            let mut state = self.state();
+            state.completed = ticket;
+            state.last_result = Some(result);
+            self.changed.notify_all();
+        }
+    }
+
+    /// Blocks until a save is due and returns the newest request it covers, or `None` once
+    /// shut down with nothing left to write.
+    fn wait_until_due(&self) -> Option<u64> {
+        let mut state = self.state();
+
+        loop {
+            match state.pending_since {
+                None if state.shutdown => return None,
+                None => {
+                    state = self
+                        .changed
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+                Some(since) => {
+                    let due = since + self.debounce;
+                    let now = Instant::now();
+
+                    if state.immediate || state.shutdown || now >= due {
+                        state.pending_since = None;
+                        state.immediate = false;
+                        return Some(state.requested);
+                    }
+
+                    state = self
+                        .changed
+                        .wait_timeout(state, due - now)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0;
+                }
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), SaveFileError> {
+        // only hold the lock while taking a snapshot, the game can keep using the save while
+        // it's encoded and written
+        let snapshot = self
+            .save_file
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+
+        let bytes = snapshot.encode(None)?;
+        snapshot.pending_write(&self.path, bytes)?.write()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The `n`th backup of `path`, e.g. `save.json.1` for the newest one.
+pub(crate) fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let mut file_name = OsString::from(path.file_name().unwrap_or_default());
+    file_name.push(format!(".{}", n));
+
+    path.with_file_name(file_name)
+}
+
+/// The backups of `path` that exist on disk, newest first.
+pub(crate) fn existing_backups(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|n| backup_path(path, n))
+        .take_while(|backup| backup.is_file())
+        .collect()
+}
+
+/// Shifts the backups of `path` up by one, dropping the oldest, and copies the current
+/// file to `.1`. The current file is left in place so there is always a complete save.
+pub(crate) fn rotate(path: &Path, count: usize) -> io::Result<()> {
+    if count == 0 || !path.is_file() {
+        return Ok(());
+    }
+
+    for n in (1..count).rev() {
+        let older = backup_path(path, n);
+
+        if older.is_file() {
+            fs::rename(&older, backup_path(path, n + 1))?;
+        }
+    }
+
+    fs::copy(path, backup_path(path, 1))?;
+
+    Ok(())
+}
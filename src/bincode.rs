@@ -0,0 +1,52 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, FormatError};
+
+/// A compact binary format using [bincode](https://docs.rs/bincode)'s standard
+/// configuration. Like `Postcard`, nothing but the values themselves is written and
+/// components are embedded as their encoded bytes. Needs the `bincode` feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bincode;
+
+impl Format for Bincode {
+    const NAME: &'static str = "bincode";
+
+    type Value = Vec<u8>;
+
+    fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Bincode::to_vec(value)
+    }
+
+    fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        Bincode::from_slice(value)
+    }
+
+    fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(::bincode::serde::encode_to_vec(
+            value,
+            ::bincode::config::standard(),
+        )?)
+    }
+
+    fn from_slice<T>(bytes: &[u8]) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        let (value, read) =
+            ::bincode::serde::decode_from_slice(bytes, ::bincode::config::standard())?;
+
+        match read == bytes.len() {
+            true => Ok(value),
+            false => Err("trailing bytes".into()),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// Runs blocking file system work on its own thread and resolves once it's done.
+///
+/// This works with any async runtime since it only relies on the `Waker` it is polled with.
+pub(crate) fn unblock<T, F>(work: F) -> Unblock<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let shared = Arc::new(Mutex::new(Shared {
+        result: None,
+        waker: None,
+    }));
+
+    let worker_shared = shared.clone();
+    thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(work));
+
+        let mut shared = worker_shared.lock().unwrap_or_else(PoisonError::into_inner);
+        shared.result = Some(result);
+
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    });
+
+    Unblock { shared }
+}
+
+pub(crate) struct Unblock<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+struct Shared<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match shared.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(panic)) => panic::resume_unwind(panic),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, FormatError};
+
+/// [CBOR](https://cbor.io) (RFC 8949), a compact binary format that still says what each
+/// value is. Components are embedded as CBOR values. Needs the `cbor` feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cbor;
+
+impl Format for Cbor {
+    const NAME: &'static str = "cbor";
+
+    type Value = ciborium::Value;
+
+    fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(ciborium::Value::serialized(value)?)
+    }
+
+    fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(value.deserialized()?)
+    }
+
+    fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    fn from_slice<T>(mut bytes: &[u8]) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        let value = ciborium::from_reader(&mut bytes)?;
+
+        match bytes.is_empty() {
+            true => Ok(value),
+            false => Err("trailing bytes".into()),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A type that knows its own key, so it can be stored with
+/// [`SaveFile::put`](crate::SaveFile::put) and read with [`SaveFile::take`](crate::SaveFile::take)
+/// without repeating the key. Usually implemented with `#[derive(SaveComponent)]` from the
+/// `derive` feature.
+pub trait SaveComponent: Serialize + DeserializeOwned {
+    const KEY: &'static str;
+
+    /// The version new data is written with, see
+    /// [`MigrationRegistry::register_upcaster`](crate::MigrationRegistry::register_upcaster).
+    const VERSION: u32 = 0;
+
+    /// Returned by `take` when the save doesn't contain this component. `None` makes a
+    /// missing component an error.
+    fn default_component() -> Option<Self> {
+        None
+    }
+}
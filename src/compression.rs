@@ -0,0 +1,43 @@
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "lz4")]
+mod lz4;
+#[cfg(feature = "zstd")]
+mod zstd;
+
+use std::{fmt::Debug, io, sync::Arc};
+
+#[cfg(feature = "gzip")]
+pub use gzip::Gzip;
+#[cfg(feature = "lz4")]
+pub use lz4::Lz4;
+#[cfg(feature = "zstd")]
+pub use zstd::Zstd;
+
+/// A codec that compresses the payload of a save file. The header stays uncompressed so
+/// it can always be read.
+///
+/// The codec's name is recorded in the header. Loading decompresses with the configured
+/// codec when the names match, and otherwise with the built-in codec of that name
+/// (`Gzip`, `Lz4` and `Zstd`, behind the `gzip`, `lz4` and `zstd` features).
+pub trait Compression: Debug + Send + Sync {
+    /// Identifies the codec in the save file header, e.g. `"zstd"`.
+    fn name(&self) -> &str;
+
+    fn compress(&self, bytes: &[u8]) -> io::Result<Vec<u8>>;
+
+    fn decompress(&self, bytes: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+/// The built-in codec called `name`, if it was compiled in.
+pub(crate) fn builtin(name: &str) -> Option<Arc<dyn Compression>> {
+    match name {
+        #[cfg(feature = "gzip")]
+        "gzip" => Some(Arc::new(Gzip)),
+        #[cfg(feature = "lz4")]
+        "lz4" => Some(Arc::new(Lz4)),
+        #[cfg(feature = "zstd")]
+        "zstd" => Some(Arc::new(Zstd)),
+        _ => None,
+    }
+}
@@ -0,0 +1,60 @@
+use std::io::{self, Read, Write};
+
+use flate2::{read::MultiGzDecoder, write::GzEncoder};
+
+use super::Compression;
+
+/// gzip (RFC 1952), readable by `gzip -d` and the like.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gzip;
+
+impl Compression for Gzip {
+    fn name(&self) -> &str {
+        "gzip"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(bytes)?;
+        encoder.finish()
+    }
+
+    // concatenated members decompress to the concatenation
+    fn decompress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        MultiGzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gzip_interoperates() {
+        let text = b"It was the best of times, it was the worst of times".repeat(40);
+
+        let compressed = Gzip.compress(&text).unwrap();
+        assert!(compressed.len() < text.len() / 10);
+        assert_eq!(Gzip.decompress(&compressed).unwrap(), text);
+
+        // from `gzip -9`, with the file name, concatenated with itself
+        let fixture = [
+            0x1F, 0x8B, 0x08, 0x08, 0x60, 0xEF, 0xD0, 0x6A, 0x02, 0x03, 0x68, 0x65, 0x6C, 0x6C,
+            0x6F, 0x2E, 0x74, 0x78, 0x74, 0x00, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40,
+            0x22, 0xD3, 0xAB, 0x32, 0x0B, 0xB8, 0x00, 0x9A, 0x8B, 0x73, 0xDA, 0x17, 0x00, 0x00,
+            0x00,
+        ]
+        .repeat(2);
+        assert_eq!(
+            Gzip.decompress(&fixture).unwrap(),
+            b"hello hello hello gzip\n".repeat(2)
+        );
+
+        let mut corrupted = fixture.clone();
+        corrupted[36] ^= 1;
+        assert!(Gzip.decompress(&corrupted).is_err());
+        assert!(Gzip.decompress(&fixture[..40]).is_err());
+    }
+}
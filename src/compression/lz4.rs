@@ -0,0 +1,76 @@
+use std::io::{self, Read, Write};
+
+use lz4_flex::frame::{FrameDecoder, FrameEncoder, FrameInfo};
+
+use super::Compression;
+
+/// LZ4 frames, readable by `lz4 -d`. Fast to compress and very fast to decompress, at a
+/// lower ratio than `Gzip` or `Zstd`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lz4;
+
+impl Compression for Lz4 {
+    fn name(&self) -> &str {
+        "lz4"
+    }
+
+    // with a content checksum, so damage is caught when loading
+    fn compress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let info = FrameInfo::new().content_checksum(true);
+        let mut encoder = FrameEncoder::with_frame_info(info, Vec::new());
+        encoder.write_all(bytes)?;
+        encoder.finish().map_err(io::Error::from)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        // lz4_flex takes input that ends between two blocks as the end of the frame, so a
+        // truncated frame would decompress without an error. Following the input with an
+        // empty frame makes a missing end mark read that frame's magic number as a block
+        // too large to be one.
+        let empty = FrameEncoder::new(Vec::new())
+            .finish()
+            .map_err(io::Error::from)?;
+
+        let mut decompressed = Vec::new();
+        FrameDecoder::new(bytes.chain(&empty[..])).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lz4_interoperates() {
+        let text = b"It was the best of times, it was the worst of times".repeat(3000);
+        let noise: Vec<u8> = (0..5000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+
+        for data in [&b""[..], b"abc", &text, &noise] {
+            let compressed = Lz4.compress(data).unwrap();
+            assert_eq!(Lz4.decompress(&compressed).unwrap(), data);
+        }
+        assert!(Lz4.compress(&text).unwrap().len() < text.len() / 50);
+
+        // from `lz4 -9 -BX --content-size`, with block checksums and the content size
+        let fixture = [
+            0x04, 0x22, 0x4D, 0x18, 0x7C, 0x40, 0x29, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x47, 0x14, 0x00, 0x00, 0x00, 0x3F, 0x61, 0x62, 0x63, 0x03, 0x00, 0x02, 0x44, 0x20,
+            0x6C, 0x7A, 0x34, 0x04, 0x00, 0x50, 0x20, 0x6C, 0x7A, 0x34, 0x0A, 0x23, 0x92, 0x49,
+            0x34, 0x00, 0x00, 0x00, 0x00, 0xBA, 0x17, 0xAB, 0x06,
+        ];
+        assert_eq!(
+            Lz4.decompress(&fixture).unwrap(),
+            b"abcabcabcabcabcabcabcabc lz4 lz4 lz4 lz4\n"
+        );
+
+        let mut corrupted = fixture;
+        corrupted[24] ^= 1;
+        assert!(Lz4.decompress(&corrupted).is_err());
+        assert!(Lz4.decompress(&fixture[..45]).is_err());
+        // cut right after the block, before the end mark
+        assert!(Lz4.decompress(&fixture[..43]).is_err());
+    }
+}
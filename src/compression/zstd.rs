@@ -0,0 +1,73 @@
+use std::io;
+
+use super::Compression;
+
+/// Zstandard (RFC 8878), readable by `zstd -d`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zstd;
+
+impl Compression for Zstd {
+    fn name(&self) -> &str {
+        "zstd"
+    }
+
+    fn compress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        // 0 picks zstd's default level
+        ::zstd::encode_all(bytes, 0)
+    }
+
+    fn decompress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        ::zstd::decode_all(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zstd_round_trip() {
+        let text = b"It was the best of times, it was the worst of times".repeat(5000);
+        let noise: Vec<u8> = (0..5000u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect();
+
+        for data in [&b""[..], b"abc", &text, &noise] {
+            let compressed = Zstd.compress(data).unwrap();
+            assert_eq!(Zstd.decompress(&compressed).unwrap(), data);
+        }
+        assert!(Zstd.compress(&text).unwrap().len() < text.len() / 50);
+    }
+
+    #[test]
+    fn test_zstd_reads_the_zstd_tool() {
+        // from `zstd -19`, with Huffman coded literals and FSE coded sequences
+        let fixture = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x24, 0xE3, 0xAD, 0x04, 0x00, 0x82, 0x8C, 0x20, 0x15, 0x90,
+            0xAB, 0xC5, 0x00, 0x36, 0x49, 0xC0, 0xC9, 0xCA, 0x90, 0x08, 0xD5, 0x4A, 0xBB, 0x61,
+            0xAE, 0x5B, 0xEE, 0xFA, 0x97, 0x1C, 0x07, 0x07, 0x2A, 0x45, 0xF4, 0x47, 0xDD, 0x9A,
+            0xF3, 0xA1, 0xFD, 0x6E, 0xD9, 0xCB, 0x68, 0x61, 0x63, 0xB2, 0xF5, 0x81, 0x8C, 0x3A,
+            0x0F, 0x2F, 0x4A, 0x18, 0x45, 0xFB, 0x30, 0x70, 0x4A, 0xD1, 0x3A, 0xF5, 0x53, 0xDE,
+            0x75, 0xEA, 0x36, 0xD3, 0xBA, 0x2B, 0x43, 0xC6, 0x48, 0xAB, 0x2C, 0xA1, 0xD5, 0xFC,
+            0x48, 0x1B, 0xB8, 0xA5, 0x98, 0xE5, 0x47, 0x39, 0xD3, 0xCF, 0xA5, 0x2A, 0x6B, 0xAE,
+            0xF4, 0xC3, 0xDD, 0x84, 0x27, 0x99, 0x1A, 0x43, 0x03, 0x0F, 0xDE, 0x4A, 0xC6, 0xA9,
+            0xE5, 0x24, 0x15, 0x77, 0x14, 0x7D, 0x64, 0x52, 0x56, 0xD7, 0x5C, 0x0E, 0x68, 0x62,
+            0x2F, 0x65, 0xF7, 0x7C, 0x8A, 0x12, 0x07, 0x78, 0xA6, 0xA0, 0x35, 0x7C, 0xCA, 0x88,
+            0x26, 0x04, 0x05, 0x00, 0x36, 0x33, 0x80, 0x78, 0x9A, 0xA1, 0x85, 0x40, 0x46, 0x32,
+            0x70, 0x10, 0xE0, 0x28, 0x3C, 0x62, 0x12, 0xB2,
+        ];
+        let text = b"Call me Ishmael. Some years ago, never mind how long precisely, having little or no money in my purse, and nothing particular to interest me on shore, I thought I would sail about a little and see the watery part of the world. ";
+        assert_eq!(Zstd.decompress(&fixture).unwrap(), text);
+
+        // with a skippable frame between two copies
+        let mut frames = fixture.to_vec();
+        frames.extend_from_slice(&[0x50, 0x2A, 0x4D, 0x18, 2, 0, 0, 0, 0xAB, 0xCD]);
+        frames.extend_from_slice(&fixture);
+        assert_eq!(Zstd.decompress(&frames).unwrap(), text.repeat(2));
+
+        let mut corrupted = fixture;
+        corrupted[40] ^= 1;
+        assert!(Zstd.decompress(&corrupted).is_err());
+        assert!(Zstd.decompress(&fixture[..100]).is_err());
+    }
+}
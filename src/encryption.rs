@@ -0,0 +1,28 @@
+#[cfg(feature = "aes-gcm")]
+mod aes_gcm;
+#[cfg(feature = "chacha20poly1305")]
+mod chacha20poly1305;
+#[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+mod key;
+
+#[cfg(feature = "aes-gcm")]
+pub use aes_gcm::Aes256Gcm;
+#[cfg(feature = "chacha20poly1305")]
+pub use chacha20poly1305::ChaCha20Poly1305;
+
+/// An authenticated cipher (such as `Aes256Gcm` or `ChaCha20Poly1305`, behind the
+/// `aes-gcm` and `chacha20poly1305` features) used by
+/// [`save_to_file_encrypted`](crate::SaveFile::save_to_file_encrypted) and
+/// [`load_from_file_encrypted`](crate::SaveFile::load_from_file_encrypted).
+///
+/// Implementations own their key, whether it is supplied directly or derived from a
+/// passphrase, and include whatever nonce or salt they need in the ciphertext.
+pub trait Cipher {
+    /// Identifies the cipher in the save file header, e.g. `"aes-256-gcm"`.
+    fn name(&self) -> &str;
+
+    fn encrypt(&self, plaintext: &[u8]) -> std::io::Result<Vec<u8>>;
+
+    /// Returns `None` when the key is wrong or the ciphertext was modified.
+    fn decrypt(&self, ciphertext: &[u8]) -> Option<Vec<u8>>;
+}
@@ -0,0 +1,109 @@
+use std::io;
+
+use sha2::Sha256;
+
+pub(crate) const NONCE: usize = 12;
+const SALT: usize = 16;
+
+/// The key of one of the built-in ciphers, and how to lay out its ciphertexts: the salt
+/// and round count when the key comes from a passphrase, then a random nonce, then
+/// whatever the cipher produces.
+#[derive(Clone)]
+pub(crate) struct Key {
+    key: [u8; 32],
+    passphrase: Option<Passphrase>,
+}
+
+#[derive(Clone)]
+struct Passphrase {
+    passphrase: String,
+    salt: [u8; SALT],
+    rounds: u32,
+}
+
+impl Key {
+    pub(crate) fn new(key: [u8; 32]) -> Self {
+        Key {
+            key,
+            passphrase: None,
+        }
+    }
+
+    /// Derives the key with PBKDF2-HMAC-SHA256 under a fresh random salt.
+    pub(crate) fn from_passphrase(passphrase: &str, rounds: u32) -> io::Result<Self> {
+        let salt = random()?;
+
+        Ok(Key {
+            key: derive(passphrase, &salt, rounds),
+            passphrase: Some(Passphrase {
+                passphrase: passphrase.to_string(),
+                salt,
+                rounds,
+            }),
+        })
+    }
+
+    pub(crate) fn encrypt(
+        &self,
+        plaintext: &[u8],
+        seal: impl FnOnce(&[u8; 32], &[u8; NONCE], &[u8]) -> Option<Vec<u8>>,
+    ) -> io::Result<Vec<u8>> {
+        let mut ciphertext = Vec::new();
+        if let Some(passphrase) = &self.passphrase {
+            ciphertext.extend_from_slice(&passphrase.salt);
+            ciphertext.extend_from_slice(&passphrase.rounds.to_be_bytes());
+        }
+
+        let nonce = random()?;
+        ciphertext.extend_from_slice(&nonce);
+        let sealed = seal(&self.key, &nonce, plaintext).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "plaintext is too long to encrypt",
+            )
+        })?;
+        ciphertext.extend(sealed);
+
+        Ok(ciphertext)
+    }
+
+    pub(crate) fn decrypt(
+        &self,
+        ciphertext: &[u8],
+        open: impl FnOnce(&[u8; 32], &[u8; NONCE], &[u8]) -> Option<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        let (key, rest) = match &self.passphrase {
+            None => (self.key, ciphertext),
+            Some(passphrase) => {
+                let (salt, rest) = ciphertext.split_first_chunk::<SALT>()?;
+                let (rounds, rest) = rest.split_first_chunk::<4>()?;
+                let rounds = u32::from_be_bytes(*rounds);
+
+                // a file can't make loading slower than the rounds this key was set up with
+                if rounds > passphrase.rounds {
+                    return None;
+                }
+
+                let key = match (salt, rounds) == (&passphrase.salt, passphrase.rounds) {
+                    true => self.key,
+                    false => derive(&passphrase.passphrase, salt, rounds),
+                };
+                (key, rest)
+            }
+        };
+
+        let (nonce, sealed) = rest.split_first_chunk::<NONCE>()?;
+        open(&key, nonce, sealed)
+    }
+}
+
+fn derive(passphrase: &str, salt: &[u8], rounds: u32) -> [u8; 32] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, 32>(passphrase.as_bytes(), salt, rounds)
+}
+
+fn random<const N: usize>() -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    getrandom::getrandom(&mut bytes).map_err(io::Error::from)?;
+
+    Ok(bytes)
+}
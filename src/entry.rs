@@ -0,0 +1,52 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, Json, SaveFile, SaveFileError};
+
+/// A component slot in a [`SaveFile`] that may or may not be filled, from
+/// [`SaveFile::entry`].
+pub struct Entry<'a, F: Format = Json> {
+    save_file: &'a mut SaveFile<F>,
+    key: String,
+}
+
+impl<'a, F: Format> Entry<'a, F> {
+    pub(crate) fn new(save_file: &'a mut SaveFile<F>, key: String) -> Self {
+        Entry { save_file, key }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Reads the component, first storing `default` if it's missing.
+    pub fn or_insert<T>(self, default: T) -> Result<T, SaveFileError>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.or_insert_with(|| default)
+    }
+
+    /// Reads the component, first storing the result of `default` if it's missing.
+    pub fn or_insert_with<T, D>(self, default: D) -> Result<T, SaveFileError>
+    where
+        T: Serialize + DeserializeOwned,
+        D: FnOnce() -> T,
+    {
+        if self.save_file.contains_key(&self.key) {
+            return self.save_file.get_component(&self.key);
+        }
+
+        let value = default();
+        self.save_file.insert_component(self.key, &value)?;
+
+        Ok(value)
+    }
+
+    /// Reads the component, first storing `T::default()` if it's missing.
+    pub fn or_default<T>(self) -> Result<T, SaveFileError>
+    where
+        T: Serialize + DeserializeOwned + Default,
+    {
+        self.or_insert_with(T::default)
+    }
+}
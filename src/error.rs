@@ -0,0 +1,199 @@
+use std::{error::Error, fmt, io};
+
+use crate::{FormatError, RecoveryReport};
+
+/// Everything that can go wrong while reading or writing a [`SaveFile`](crate::SaveFile).
+#[derive(Debug)]
+pub enum SaveFileError {
+    /// No component is stored under this key.
+    MissingKey(String),
+    /// The component exists but was stored as a different type or could not be decoded as
+    /// the requested one. `stored` is missing for components from older saves.
+    TypeMismatch {
+        key: String,
+        stored: Option<String>,
+        requested: String,
+        source: Option<FormatError>,
+    },
+    /// Reading or writing the file on disk failed.
+    Io(io::Error),
+    /// The platform has no directory to put save files in.
+    NoSaveDir,
+    /// The slot name is empty or isn't a plain file name.
+    InvalidSlotName(String),
+    /// No save slot with this name exists.
+    SlotNotFound(String),
+    /// A save slot with this name already exists.
+    SlotExists(String),
+    /// The save file itself could not be encoded or decoded.
+    Format(FormatError),
+    /// The file doesn't start with a save file header and isn't a legacy save either.
+    NotASaveFile,
+    /// The file was written with a newer container layout than this version of the crate reads.
+    UnsupportedContainerVersion { found: u32, supported: u32 },
+    /// The payload was written with a different format than the one it's being loaded with.
+    EncodingMismatch {
+        found: String,
+        expected: &'static str,
+    },
+    /// The payload is compressed with a codec that is neither configured nor built in.
+    UnsupportedCompression(String),
+    /// The payload could not be decompressed, so the file is damaged.
+    Decompression(io::Error),
+    /// The payload is encrypted with a different cipher than the one it's being loaded with,
+    /// or it's encrypted and no cipher was given.
+    UnsupportedEncryption(String),
+    /// The payload could not be decrypted, either the key is wrong or the file was modified.
+    DecryptionFailed,
+    /// Some components are damaged. `SaveFile::recover_from_file` loads the rest.
+    Corrupt(RecoveryReport),
+    /// The save file's signature is missing or doesn't match its contents.
+    Tampered,
+    /// The save file was written with a newer schema version than this build supports.
+    UnsupportedVersion { found: u32, supported: u32 },
+    /// A component was written with a newer version than its registered upcasters reach.
+    UnsupportedComponentVersion {
+        key: String,
+        found: u32,
+        supported: u32,
+    },
+    /// A component needs upgrading but no upcaster is registered for one of the steps.
+    MissingUpcaster { key: String, from_version: u32 },
+}
+
+impl SaveFileError {
+    /// True if the file's contents are damaged, as opposed to being unreadable or
+    /// intact but unsupported.
+    pub(crate) fn is_damaged(&self) -> bool {
+        matches!(
+            self,
+            SaveFileError::Format(_)
+                | SaveFileError::Decompression(_)
+                | SaveFileError::Corrupt(_)
+                | SaveFileError::NotASaveFile
+                | SaveFileError::DecryptionFailed
+        )
+    }
+}
+
+impl fmt::Display for SaveFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveFileError::MissingKey(key) => write!(f, "no component stored under key '{}'", key),
+            SaveFileError::TypeMismatch {
+                key,
+                stored,
+                requested,
+                source,
+            } => {
+                write!(f, "component '{}' could not be read as {}", key, requested)?;
+
+                if let Some(stored) = stored {
+                    write!(f, ", it was stored as {}", stored)?;
+                }
+
+                match source {
+                    Some(source) => write!(f, ": {}", source),
+                    None => Ok(()),
+                }
+            }
+            SaveFileError::Io(err) => write!(f, "i/o error: {}", err),
+            SaveFileError::NoSaveDir => write!(f, "no directory is available to store save files"),
+            SaveFileError::InvalidSlotName(name) => {
+                write!(f, "'{}' is not a valid slot name", name)
+            }
+            SaveFileError::SlotNotFound(name) => write!(f, "save slot '{}' does not exist", name),
+            SaveFileError::SlotExists(name) => write!(f, "save slot '{}' already exists", name),
+            SaveFileError::Format(err) => write!(f, "invalid save file: {}", err),
+            SaveFileError::NotASaveFile => write!(f, "the file is not a save file"),
+            SaveFileError::UnsupportedContainerVersion { found, supported } => write!(
+                f,
+                "save file has container version {} but only versions up to {} are supported",
+                found, supported
+            ),
+            SaveFileError::EncodingMismatch { found, expected } => write!(
+                f,
+                "save file is encoded as '{}' but was loaded as '{}'",
+                found, expected
+            ),
+            SaveFileError::UnsupportedCompression(name) => write!(
+                f,
+                "save file is compressed with '{}' which is not available",
+                name
+            ),
+            SaveFileError::Decompression(err) => {
+                write!(f, "save file could not be decompressed: {}", err)
+            }
+            SaveFileError::UnsupportedEncryption(name) => write!(
+                f,
+                "save file is encrypted with '{}' which was not provided",
+                name
+            ),
+            SaveFileError::DecryptionFailed => write!(
+                f,
+                "save file could not be decrypted, the key is wrong or the file was modified"
+            ),
+            SaveFileError::Corrupt(report) => write!(f, "save file is damaged, {}", report),
+            SaveFileError::Tampered => write!(f, "save file was modified or is not signed"),
+            SaveFileError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "save file has schema version {} but only versions up to {} are supported",
+                found, supported
+            ),
+            SaveFileError::UnsupportedComponentVersion {
+                key,
+                found,
+                supported,
+            } => write!(
+                f,
+                "component '{}' has version {} but only versions up to {} are supported",
+                key, found, supported
+            ),
+            SaveFileError::MissingUpcaster { key, from_version } => write!(
+                f,
+                "no upcaster registered for component '{}' at version {}",
+                key, from_version
+            ),
+        }
+    }
+}
+
+impl Error for SaveFileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveFileError::MissingKey(_)
+            | SaveFileError::NoSaveDir
+            | SaveFileError::InvalidSlotName(_)
+            | SaveFileError::SlotNotFound(_)
+            | SaveFileError::SlotExists(_)
+            | SaveFileError::NotASaveFile
+            | SaveFileError::UnsupportedContainerVersion { .. }
+            | SaveFileError::EncodingMismatch { .. }
+            | SaveFileError::UnsupportedCompression(_)
+            | SaveFileError::UnsupportedEncryption(_)
+            | SaveFileError::DecryptionFailed
+            | SaveFileError::Corrupt(_)
+            | SaveFileError::Tampered
+            | SaveFileError::UnsupportedVersion { .. }
+            | SaveFileError::UnsupportedComponentVersion { .. }
+            | SaveFileError::MissingUpcaster { .. } => None,
+            SaveFileError::TypeMismatch { source, .. } => {
+                source.as_deref().map(|err| err as &(dyn Error + 'static))
+            }
+            SaveFileError::Io(err) | SaveFileError::Decompression(err) => Some(err),
+            SaveFileError::Format(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for SaveFileError {
+    fn from(err: io::Error) -> Self {
+        SaveFileError::Io(err)
+    }
+}
+
+impl From<FormatError> for SaveFileError {
+    fn from(err: FormatError) -> Self {
+        SaveFileError::Format(err)
+    }
+}
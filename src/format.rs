@@ -0,0 +1,113 @@
+use std::{error::Error, fmt::Debug};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::lines;
+
+/// The error type returned by a [`Format`].
+pub type FormatError = Box<dyn Error + Send + Sync>;
+
+/// What [`Format::salvage`] could still read of a damaged save file.
+#[derive(Debug, Clone)]
+pub struct Salvage<V> {
+    /// Every component in the order it was written, `None` where its value couldn't be
+    /// decoded. Components whose key couldn't be read either are keyed by their raw text.
+    pub components: Vec<(String, Option<V>)>,
+    /// The rest of the save file, without its components.
+    pub rest: V,
+}
+
+/// The encoding used for component payloads and for the save file written to disk.
+pub trait Format {
+    /// Identifies the format in the save file header, e.g. `"json"`.
+    const NAME: &'static str;
+
+    /// How a single component is held in memory and embedded in the save file.
+    type Value: Serialize + DeserializeOwned + Clone + Debug + Send + Sync;
+
+    fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+    where
+        T: Serialize + ?Sized;
+
+    fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned;
+
+    fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized;
+
+    fn from_slice<T>(bytes: &[u8]) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned;
+
+    /// Encodes a whole save file, the same as `to_vec` unless the format lays it out so
+    /// `salvage` can find its components one at a time.
+    fn to_document<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Self::to_vec(value)
+    }
+
+    /// Reads what it can of a save file written by `to_document` that `from_slice`
+    /// couldn't decode. `None`, the default, if nothing can be salvaged.
+    fn salvage(_bytes: &[u8]) -> Option<Salvage<Self::Value>> {
+        None
+    }
+}
+
+/// Human readable JSON, the default format. Components are embedded as plain JSON values,
+/// each on its own line so a damaged one doesn't take the others with it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+impl Format for Json {
+    const NAME: &'static str = "json";
+
+    type Value = serde_json::Value;
+
+    fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(serde_json::to_value(value)?)
+    }
+
+    fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(T::deserialize(value)?)
+    }
+
+    fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(serde_json::to_vec(value)?)
+    }
+
+    fn from_slice<T>(bytes: &[u8]) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    fn to_document<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut bytes = Vec::new();
+        let mut serializer =
+            serde_json::Serializer::with_formatter(&mut bytes, lines::Lines::default());
+        value.serialize(&mut serializer)?;
+
+        Ok(bytes)
+    }
+
+    fn salvage(bytes: &[u8]) -> Option<Salvage<Self::Value>> {
+        lines::salvage(bytes)
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::SaveFileError;
+
+/// The bytes every save file starts with.
+pub const MAGIC: &[u8; 8] = b"ABCSAVE\n";
+
+/// The newest container layout this version of the crate reads and writes.
+pub const CONTAINER_VERSION: u32 = 3;
+
+/// Metadata written at the start of every save file, before the payload.
+///
+/// The header is a single line of JSON after [`MAGIC`] so it can be read without knowing
+/// how the payload is encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SaveHeader {
+    pub container_version: u32,
+    /// Seconds since the unix epoch when the save was first written.
+    pub created: u64,
+    /// Seconds since the unix epoch when the save was last written.
+    pub modified: u64,
+    pub game_version: String,
+    /// The [`Format::NAME`](crate::Format::NAME) of the payload.
+    pub encoding: String,
+    /// The [`Compression::name`](crate::Compression::name) of the codec the payload was
+    /// compressed with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compression: Option<String>,
+    /// The [`Cipher::name`](crate::Cipher::name) of the cipher the payload was encrypted
+    /// with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<String>,
+    /// A signature over the rest of the header and the stored payload, if the save was
+    /// signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<IntegrityBlock>,
+}
+
+/// The signature written by an [`Integrity`](crate::Integrity) implementation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityBlock {
+    pub algorithm: String,
+    #[serde(with = "hex")]
+    pub signature: Vec<u8>,
+}
+
+impl SaveHeader {
+    pub(crate) fn new(game_version: &str, encoding: &str, created: Option<u64>) -> Self {
+        let now = now();
+
+        SaveHeader {
+            container_version: CONTAINER_VERSION,
+            created: created.unwrap_or(now),
+            modified: now,
+            game_version: game_version.to_string(),
+            encoding: encoding.to_string(),
+            compression: None,
+            encryption: None,
+            integrity: None,
+        }
+    }
+
+    pub(crate) fn write(&self, payload: &[u8]) -> Result<Vec<u8>, SaveFileError> {
+        let header = serde_json::to_vec(self).map_err(|err| SaveFileError::Format(err.into()))?;
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + header.len() + 1 + payload.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&header);
+        bytes.push(b'\n');
+        bytes.extend_from_slice(payload);
+
+        Ok(bytes)
+    }
+
+    /// The bytes an [`Integrity`](crate::Integrity) signature covers: the file as written,
+    /// minus the signature itself.
+    pub(crate) fn signed_bytes(&self, payload: &[u8]) -> Result<Vec<u8>, SaveFileError> {
+        SaveHeader {
+            integrity: None,
+            ..self.clone()
+        }
+        .write(payload)
+    }
+
+    /// Splits a save file into its header and payload. Files written before headers existed
+    /// are plain JSON and come back without a header.
+    pub(crate) fn split(bytes: &[u8]) -> Result<(Option<SaveHeader>, &[u8]), SaveFileError> {
+        let Some(rest) = bytes.strip_prefix(MAGIC) else {
+            let legacy = bytes
+                .iter()
+                .find(|byte| !byte.is_ascii_whitespace())
+                .is_some_and(|&byte| byte == b'{');
+
+            return match legacy {
+                true => Ok((None, bytes)),
+                false => Err(SaveFileError::NotASaveFile),
+            };
+        };
+
+        let line_end = rest
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or(SaveFileError::NotASaveFile)?;
+
+        let header = SaveHeader::parse(&rest[..line_end])?;
+
+        Ok((Some(header), &rest[line_end + 1..]))
+    }
+
+    /// Reads only the header of the file at `path`.
+    pub fn read(path: &Path) -> Result<Option<SaveHeader>, SaveFileError> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; MAGIC.len()];
+        if reader.read_exact(&mut magic).is_err() || &magic != MAGIC {
+            return Ok(None);
+        }
+
+        let mut line = vec![];
+        reader.read_until(b'\n', &mut line)?;
+
+        SaveHeader::parse(line.strip_suffix(b"\n").unwrap_or(&line)).map(Some)
+    }
+
+    fn parse(line: &[u8]) -> Result<SaveHeader, SaveFileError> {
+        let header: SaveHeader =
+            serde_json::from_slice(line).map_err(|err| SaveFileError::Format(err.into()))?;
+
+        if header.container_version > CONTAINER_VERSION {
+            return Err(SaveFileError::UnsupportedContainerVersion {
+                found: header.container_version,
+                supported: CONTAINER_VERSION,
+            });
+        }
+
+        Ok(header)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+mod hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+        serializer.serialize_str(&hex)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+
+        if hex.len() % 2 != 0 {
+            return Err(D::Error::custom("hex string has an odd length"));
+        }
+
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    .ok_or_else(|| D::Error::custom("invalid hex string"))
+            })
+            .collect()
+    }
+}
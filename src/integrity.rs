@@ -0,0 +1,26 @@
+use std::{fmt::Debug, io};
+
+#[cfg(feature = "ed25519")]
+mod ed25519;
+#[cfg(feature = "hmac")]
+mod hmac;
+
+#[cfg(feature = "ed25519")]
+pub use ed25519::Ed25519;
+#[cfg(feature = "hmac")]
+pub use hmac::HmacSha256;
+
+/// Detects edited save files, e.g. `HmacSha256` with a game secret or an `Ed25519`
+/// signature (behind the `hmac` and `ed25519` features).
+///
+/// When set on a [`SaveFile`](crate::SaveFile), saving signs the header and the stored
+/// payload, and loading refuses files whose signature is missing or doesn't match.
+pub trait Integrity: Debug + Send + Sync {
+    /// Identifies the algorithm in the save file header, e.g. `"hmac-sha256"`.
+    fn name(&self) -> &str;
+
+    /// Verify-only keys, like an Ed25519 public key on a server, return an error.
+    fn sign(&self, data: &[u8]) -> io::Result<Vec<u8>>;
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool;
+}
@@ -0,0 +1,134 @@
+use std::{fmt, io};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+use super::Integrity;
+
+/// Ed25519 signatures (RFC 8032).
+///
+/// A server can sign saves with the private key while the game only ships the public
+/// key, so players can't sign edited saves even after extracting it.
+#[derive(Clone)]
+pub struct Ed25519 {
+    verifying: VerifyingKey,
+    signing: Option<SigningKey>,
+}
+
+impl Ed25519 {
+    /// A key that signs and verifies, from its 32 byte private key.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        let signing = SigningKey::from_bytes(&seed);
+
+        Ed25519 {
+            verifying: signing.verifying_key(),
+            signing: Some(signing),
+        }
+    }
+
+    /// A key that only verifies; signing with it fails. Errors if `public_key` isn't a
+    /// valid Ed25519 public key.
+    pub fn verifying(public_key: [u8; 32]) -> io::Result<Self> {
+        let verifying = VerifyingKey::from_bytes(&public_key)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+        Ok(Ed25519 {
+            verifying,
+            signing: None,
+        })
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.verifying.to_bytes()
+    }
+}
+
+// keeps the private key out of logs
+impl fmt::Debug for Ed25519 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ed25519")
+            .field("public_key", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Integrity for Ed25519 {
+    fn name(&self) -> &str {
+        "ed25519"
+    }
+
+    fn sign(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let signing = self.signing.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "this Ed25519 key can only verify signatures",
+            )
+        })?;
+
+        Ok(signing.sign(data).to_vec())
+    }
+
+    // strict verification also refuses non-canonical signatures and weak keys
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        Signature::from_slice(signature)
+            .is_ok_and(|signature| self.verifying.verify_strict(data, &signature).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex<const N: usize>(hex: &str) -> [u8; N] {
+        std::array::from_fn(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+    }
+
+    #[test]
+    fn test_ed25519_vectors() {
+        // RFC 8032 tests 1 and 2, then a longer message signed with Python's
+        // `cryptography`
+        let vectors: [(&str, &str, &[u8], &str); 3] = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                b"",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                b"\x72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+            (
+                "f5e5767cf153319517630f226876b86c8160cc583bc013744c6bf255f5cc0ee5",
+                "278117fc144c72340f67d0f2316e8386ceffbf2b2428c9c51fef7c597f1d426e",
+                &b"hello save file".repeat(100),
+                "21dfa4b8000c204f74f06dec60ccd181465caf357cc9c6d01734f7abbcc1ea2aac80378f409446a1bc835626873f8fb0a147972fa42f435759244f1c43ba130d",
+            ),
+        ];
+
+        for (seed, public_key, message, signature) in vectors {
+            let key = Ed25519::from_seed(unhex(seed));
+            assert_eq!(key.public_key(), unhex::<32>(public_key));
+
+            let signed = key.sign(message).unwrap();
+            assert_eq!(signed, unhex::<64>(signature));
+
+            let verifier = Ed25519::verifying(key.public_key()).unwrap();
+            assert!(verifier.verify(message, &signed));
+            assert!(verifier.sign(message).is_err());
+
+            let mut forged = signed.clone();
+            forged[10] ^= 1;
+            assert!(!verifier.verify(message, &forged));
+            assert!(!verifier.verify(b"edited", &signed));
+            assert!(!verifier.verify(message, &signed[..63]));
+        }
+
+        // not a point on the curve
+        assert!(Ed25519::verifying(unhex(
+            "0200000000000000000000000000000000000000000000000000000000000000"
+        ))
+        .is_err());
+    }
+}
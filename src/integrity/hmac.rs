@@ -0,0 +1,81 @@
+use std::{fmt, io};
+
+use ::hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::Integrity;
+
+/// HMAC-SHA256 keyed with a secret the game ships with or keeps on its server.
+///
+/// Anyone who has the key can sign saves, so this only stops edits by players who don't.
+#[derive(Clone)]
+pub struct HmacSha256 {
+    hmac: Hmac<Sha256>,
+}
+
+impl HmacSha256 {
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        HmacSha256 {
+            hmac: Hmac::new_from_slice(key.as_ref()).expect("HMAC takes keys of any length"),
+        }
+    }
+}
+
+// keeps the key out of logs
+impl fmt::Debug for HmacSha256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSha256").finish_non_exhaustive()
+    }
+}
+
+impl Integrity for HmacSha256 {
+    fn name(&self) -> &str {
+        "hmac-sha256"
+    }
+
+    fn sign(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut hmac = self.hmac.clone();
+        hmac.update(data);
+        Ok(hmac.finalize().into_bytes().to_vec())
+    }
+
+    // in constant time, so timing doesn't give away how much of a forgery was right
+    fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+        let mut hmac = self.hmac.clone();
+        hmac.update(data);
+        hmac.verify_slice(signature).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_vectors() {
+        // RFC 4231, test cases 2 and 6
+        let vectors: [(&[u8], &[u8], &str); 2] = [
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                &[0xAA; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ];
+
+        for (key, data, mac) in vectors {
+            let hmac = HmacSha256::new(key);
+            let signed = hmac.sign(data).unwrap();
+            let hex: String = signed.iter().map(|byte| format!("{:02x}", byte)).collect();
+            assert_eq!(hex, mac);
+
+            assert!(hmac.verify(data, &signed));
+            assert!(!hmac.verify(data, &signed[..31]));
+            assert!(!HmacSha256::new("other key").verify(data, &signed));
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use std::{fmt, marker::PhantomData};
+
+/// A component key that also fixes the component's type, so reading it back as the wrong
+/// type doesn't compile, e.g. `const HEALTH: SaveKey<i32> = SaveKey::new("player health");`.
+pub struct SaveKey<T> {
+    name: &'static str,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T> SaveKey<T> {
+    pub const fn new(name: &'static str) -> Self {
+        SaveKey {
+            name,
+            ty: PhantomData,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for SaveKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SaveKey<T> {}
+
+impl<T> fmt::Debug for SaveKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SaveKey")
+            .field(&self.name)
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
@@ -1,22 +1,138 @@
+#![allow(non_snake_case)]
+
 use std::{
-    fs::{create_dir_all, OpenOptions},
-    io::{self, Write},
+    borrow::Cow,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use rustc_hash::FxHashMap;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+mod atomic;
+mod autosave;
+mod backup;
+#[cfg(feature = "bincode")]
+mod bincode;
+mod blocking;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod checksum;
+mod component;
+mod compression;
+mod encryption;
+mod entry;
+mod error;
+mod format;
+mod header;
+mod integrity;
+mod key;
+mod lines;
+mod location;
+mod meta;
+mod migration;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod namespace;
+mod packed;
+#[cfg(feature = "postcard")]
+mod postcard;
+mod raw;
+mod slots;
+mod tuple;
+
+pub use autosave::{AutoSaver, SaveResult};
+#[cfg(feature = "bincode")]
+pub use bincode::Bincode;
+#[cfg(feature = "cbor")]
+pub use cbor::Cbor;
+pub use component::SaveComponent;
+pub use compression::Compression;
+#[cfg(feature = "gzip")]
+pub use compression::Gzip;
+#[cfg(feature = "lz4")]
+pub use compression::Lz4;
+#[cfg(feature = "zstd")]
+pub use compression::Zstd;
+#[cfg(feature = "aes-gcm")]
+pub use encryption::Aes256Gcm;
+#[cfg(feature = "chacha20poly1305")]
+pub use encryption::ChaCha20Poly1305;
+pub use encryption::Cipher;
+pub use entry::Entry;
+pub use error::SaveFileError;
+pub use format::{Format, FormatError, Json, Salvage};
+pub use header::{IntegrityBlock, SaveHeader, CONTAINER_VERSION, MAGIC};
+#[cfg(feature = "ed25519")]
+pub use integrity::Ed25519;
+#[cfg(feature = "hmac")]
+pub use integrity::HmacSha256;
+pub use integrity::Integrity;
+pub use key::SaveKey;
+pub use location::SaveLocation;
+pub use migration::{Migration, MigrationRegistry};
+#[cfg(feature = "msgpack")]
+pub use msgpack::MsgPack;
+pub use namespace::Namespace;
+#[cfg(feature = "postcard")]
+pub use postcard::Postcard;
+pub use raw::RecoveryReport;
+pub use slots::{SaveSlots, SlotInfo};
+pub use tuple::ComponentTuple;
+
+#[cfg(feature = "derive")]
+pub use ABC_Save_Files_derive::SaveComponent;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SaveFile {
-    map: FxHashMap<String, Vec<u8>>,
+// lets the derive macro's `::ABC_Save_Files` paths resolve inside this crate's tests
+#[cfg(test)]
+extern crate self as ABC_Save_Files;
+
+use atomic::PendingWrite;
+use meta::{tags_match, type_tag, ComponentMeta};
+use raw::{Document, LegacySaveFile, RawSaveFile, TableSaveFile};
+
+#[derive(Deserialize, Debug)]
+#[serde(bound = "", try_from = "RawSaveFile<F>")]
+pub struct SaveFile<F: Format = Json> {
+    map: FxHashMap<String, F::Value>,
     org_name: String,
+    version: u32,
+    meta: FxHashMap<String, ComponentMeta>,
+    migrations: MigrationRegistry<F>,
+    location: SaveLocation,
+    game_version: String,
+    compression: Option<Arc<dyn Compression>>,
+    integrity: Option<Arc<dyn Integrity>>,
+    backups: usize,
+    checksums: bool,
+    header: Option<SaveHeader>,
+    format: PhantomData<fn() -> F>,
 }
 
 impl SaveFile {
     pub fn new(orginization_name: String) -> Self {
+        SaveFile::with_format(orginization_name)
+    }
+}
+
+impl<F: Format> SaveFile<F> {
+    /// Creates a save file that encodes its components and itself with `F`.
+    pub fn with_format(orginization_name: String) -> Self {
         SaveFile {
             map: FxHashMap::default(),
             org_name: orginization_name,
+            version: 0,
+            meta: FxHashMap::default(),
+            migrations: MigrationRegistry::default(),
+            location: SaveLocation::default(),
+            game_version: String::new(),
+            compression: None,
+            integrity: None,
+            backups: 0,
+            checksums: true,
+            header: None,
+            format: PhantomData,
         }
     }
 
@@ -24,86 +140,713 @@ impl SaveFile {
         self.org_name = name;
     }
 
-    pub fn add_component<'a, T>(&mut self, key: String, value: T) -> Result<(), serde_json::Error>
+    pub fn location(&self) -> &SaveLocation {
+        &self.location
+    }
+
+    /// Chooses the directory `get_save_dir` resolves to, the user's data directory by default.
+    pub fn set_location(&mut self, location: SaveLocation) {
+        self.location = location;
+    }
+
+    /// The version of the game writing this save, recorded in the file header.
+    pub fn set_game_version(&mut self, game_version: &str) {
+        self.game_version = game_version.to_string();
+    }
+
+    /// Compresses the payload with `compression` when saving. Loading reads the codec from
+    /// the file header, and falls back to the built-in codec of that name when it isn't
+    /// this one, so files compressed with `Gzip`, `Lz4` or `Zstd` load without setting it.
+    pub fn set_compression(&mut self, compression: Option<Arc<dyn Compression>>) {
+        self.compression = compression;
+    }
+
+    /// Signs saved files with `integrity`, and makes loading reject files that are unsigned
+    /// or whose signature doesn't verify with `SaveFileError::Tampered`.
+    pub fn set_integrity(&mut self, integrity: Option<Arc<dyn Integrity>>) {
+        self.integrity = integrity;
+    }
+
+    /// Keeps the last `count` versions of every file this save is written to, as
+    /// `<file>.1` (the newest) up to `<file>.<count>`.
+    pub fn set_backups(&mut self, count: usize) {
+        self.backups = count;
+    }
+
+    /// Whether a CRC-32 of every component is written so damage can be detected on
+    /// load, on by default. Each one is written next to its component and costs a few
+    /// bytes in the file.
+    pub fn set_checksums(&mut self, enabled: bool) {
+        self.checksums = enabled;
+    }
+
+    /// The header of the file this save was loaded from, `None` for new and legacy saves.
+    pub fn header(&self) -> Option<&SaveHeader> {
+        self.header.as_ref()
+    }
+
+    /// The schema version this save file is written with.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn set_version(&mut self, version: u32) {
+        self.version = version;
+    }
+
+    /// The migrations `load_from_file` runs to bring older saves up to `version()`.
+    pub fn set_migrations(&mut self, migrations: MigrationRegistry<F>) {
+        self.migrations = migrations;
+    }
+
+    pub fn add_component<'a, T>(&mut self, key: String, value: T) -> Result<(), SaveFileError>
     where
         T: Serialize + Deserialize<'a>,
     {
-        let serialized = serde_json::to_vec(&value)?;
+        self.insert_component(key, &value)
+    }
+
+    fn insert_component<T: Serialize>(
+        &mut self,
+        key: String,
+        value: &T,
+    ) -> Result<(), SaveFileError> {
+        let version = self.migrations.component_version(&key);
+
+        self.insert_versioned(key, value, version)
+    }
+
+    fn insert_versioned<T: Serialize>(
+        &mut self,
+        key: String,
+        value: &T,
+        version: u32,
+    ) -> Result<(), SaveFileError> {
+        let serialized = F::to_value(value)?;
+
+        self.insert_value(key, serialized, version, type_tag::<T>())
+    }
+
+    fn insert_value(
+        &mut self,
+        key: String,
+        value: F::Value,
+        version: u32,
+        type_name: String,
+    ) -> Result<(), SaveFileError> {
+        let meta = ComponentMeta {
+            version,
+            type_name: Some(type_name),
+        };
+
+        if meta.is_default() {
+            self.meta.remove(&key);
+        } else {
+            self.meta.insert(key.clone(), meta);
+        }
 
-        self.map.insert(key, serialized);
+        self.map.insert(key, value);
 
         Ok(())
     }
 
-    pub fn get_component<'a, T>(&'a self, key: &str) -> Result<T, serde_json::Error>
+    /// Reads a component back as `T`. It can be read as any type serde reads from what was
+    /// written, so a `Vec<u8>` also comes back as an `Option<Vec<u8>>` or a `VecDeque<u8>`,
+    /// as long as the format can tell them apart.
+    pub fn get_component<T>(&self, key: &str) -> Result<T, SaveFileError>
     where
-        T: Serialize + Deserialize<'a>,
+        T: DeserializeOwned,
+    {
+        let serialized = self
+            .map
+            .get(key)
+            .ok_or_else(|| SaveFileError::MissingKey(key.to_string()))?;
+
+        self.check_type::<T>(key)?;
+
+        F::from_value(serialized).map_err(|source| self.type_mismatch::<T>(key, Some(source)))
+    }
+
+    /// Fails if the component was written as a different type than `T`, apart from an added
+    /// or dropped `Option` or a different collection of the same kind. Components from
+    /// older saves have no type recorded and reading them as the format's own value type
+    /// always succeeds.
+    fn check_type<T>(&self, key: &str) -> Result<(), SaveFileError> {
+        if std::any::type_name::<T>() == std::any::type_name::<F::Value>() {
+            return Ok(());
+        }
+
+        match self.stored_type(key) {
+            Some(stored) if !tags_match(&stored, &type_tag::<T>()) => {
+                Err(self.type_mismatch::<T>(key, None))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn stored_type(&self, key: &str) -> Option<String> {
+        self.type_name(key).map(str::to_string)
+    }
+
+    pub(crate) fn type_name(&self, key: &str) -> Option<&str> {
+        self.meta.get(key)?.type_name.as_deref()
+    }
+
+    fn type_mismatch<T>(&self, key: &str, source: Option<FormatError>) -> SaveFileError {
+        SaveFileError::TypeMismatch {
+            key: key.to_string(),
+            stored: self.stored_type(key),
+            requested: type_tag::<T>(),
+            source,
+        }
+    }
+
+    /// Like `get_component`, but a missing component comes back as `T::default()`.
+    pub fn get_component_or_default<T>(&self, key: &str) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned + Default,
+    {
+        self.get_component_or_else(key, T::default)
+    }
+
+    /// Like `get_component`, but a missing component comes back as `fallback`.
+    pub fn get_component_or<T>(&self, key: &str, fallback: T) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned,
+    {
+        self.get_component_or_else(key, || fallback)
+    }
+
+    fn get_component_or_else<T, D>(&self, key: &str, fallback: D) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned,
+        D: FnOnce() -> T,
+    {
+        match self.get_component(key) {
+            Err(SaveFileError::MissingKey(_)) => Ok(fallback()),
+            result => result,
+        }
+    }
+
+    /// Decodes the component under `key`, lets `update` change it and stores the result.
+    /// The stored component is left untouched if decoding or encoding fails.
+    pub fn update_component<T, R, U>(&mut self, key: &str, update: U) -> Result<R, SaveFileError>
+    where
+        T: Serialize + DeserializeOwned,
+        U: FnOnce(&mut T) -> R,
+    {
+        let mut value: T = self.get_component_versioned(key)?;
+        let result = update(&mut value);
+        let version = self.current_version(key);
+
+        self.insert_versioned(key.to_string(), &value, version)?;
+
+        Ok(result)
+    }
+
+    /// A view of the components under `path`, e.g. `"levels/forest"`. Its keys are
+    /// stored as `"levels/forest/<key>"` and written as a nested map.
+    pub fn namespace(&mut self, path: &str) -> Namespace<'_, F> {
+        Namespace::new(self, path)
+    }
+
+    /// The component slot under `key`, for reading it or filling it in if it's missing.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_, F> {
+        Entry::new(self, key.into())
+    }
+
+    /// Stores `value` under a typed key.
+    pub fn set<T>(&mut self, key: &SaveKey<T>, value: T) -> Result<(), SaveFileError>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.add_component(key.name().to_string(), value)
+    }
+
+    /// Reads the component under a typed key.
+    pub fn get<T>(&self, key: &SaveKey<T>) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned,
     {
-        let serialized = self.map.get(key).unwrap();
+        self.get_component(key.name())
+    }
+
+    /// Stores a component under its own key and version.
+    pub fn put<T: SaveComponent>(&mut self, value: &T) -> Result<(), SaveFileError> {
+        let version = self.declared_version::<T>()?;
+
+        self.insert_versioned(T::KEY.to_string(), value, version)
+    }
+
+    /// Reads a component stored under its own key, upcasting it if it was written with an
+    /// older version. Missing components fall back to `T::default_component()`.
+    pub fn take<T: SaveComponent>(&self) -> Result<T, SaveFileError> {
+        if !self.contains_key(T::KEY) {
+            return T::default_component()
+                .ok_or_else(|| SaveFileError::MissingKey(T::KEY.to_string()));
+        }
 
-        let deserialized: T = serde_json::from_slice(&serialized)?;
+        let version = self.declared_version::<T>()?;
 
-        Ok(deserialized)
+        self.read_versioned(T::KEY, version)
     }
 
-    pub fn get_save_dir(&self) -> String {
-        let data_dir = match dirs::data_dir() {
-            Some(path) => {
-                // should never be none because it would have to be invalid unicode
-                path.to_str()
-                    .expect("data dir from 'dirs' crate is invalid")
-                    .to_string()
+    // `T::VERSION`, which must agree with the upcasters registered for `T::KEY`
+    fn declared_version<T: SaveComponent>(&self) -> Result<u32, SaveFileError> {
+        match self.migrations.current_version(T::KEY) {
+            Some(supported) if supported != T::VERSION => {
+                Err(SaveFileError::UnsupportedComponentVersion {
+                    key: T::KEY.to_string(),
+                    found: T::VERSION,
+                    supported,
+                })
             }
-            None => "".to_string(),
+            _ => Ok(T::VERSION),
+        }
+    }
+
+    // the version set by the upcasters for `key`, or the stored one if it has none
+    fn current_version(&self, key: &str) -> u32 {
+        self.migrations
+            .current_version(key)
+            .unwrap_or_else(|| self.component_version(key))
+    }
+
+    /// The version the component under `key` was written with.
+    pub fn component_version(&self, key: &str) -> u32 {
+        self.meta.get(key).map_or(0, |meta| meta.version)
+    }
+
+    /// Like `get_component`, but first runs the upcasters registered for `key` to bring
+    /// the stored data from the version it was written with up to the current one.
+    /// Keys without upcasters are read as they were stored.
+    pub fn get_component_versioned<T>(&self, key: &str) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned,
+    {
+        self.read_versioned(key, self.current_version(key))
+    }
+
+    fn read_versioned<T>(&self, key: &str, current: u32) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned,
+    {
+        let serialized = self
+            .map
+            .get(key)
+            .ok_or_else(|| SaveFileError::MissingKey(key.to_string()))?;
+
+        let found = self.component_version(key);
+
+        // the recorded type is the old one when the component still needs upcasting
+        if found == current {
+            self.check_type::<T>(key)?;
+        }
+
+        let upcast = self.migrations.upcast(key, found, current, serialized)?;
+
+        F::from_value(&upcast).map_err(|source| self.type_mismatch::<T>(key, Some(source)))
+    }
+
+    /// Removes a component, returning its stored value if it existed.
+    pub fn remove_component(&mut self, key: &str) -> Option<F::Value> {
+        self.meta.remove(key);
+        self.map.remove(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    /// The keys of every stored component, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(String::as_str)
+    }
+
+    /// Every stored component with its encoded value, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &F::Value)> {
+        self.map.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// The number of stored components.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Removes every component.
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.meta.clear();
+    }
+
+    /// Keeps only the components for which `keep` returns true.
+    pub fn retain<R>(&mut self, mut keep: R)
+    where
+        R: FnMut(&str, &F::Value) -> bool,
+    {
+        self.map.retain(|key, value| keep(key, value));
+
+        let map = &self.map;
+        self.meta.retain(|key, _| map.contains_key(key));
+    }
+
+    /// Reads several components at once, e.g.
+    /// `get_components::<(Health, Mana, Inventory)>(["health", "mana", "inventory"])`.
+    /// Components that are missing or can't be decoded come back as `None`.
+    pub fn get_components<T>(&self, keys: T::Keys<'_>) -> T::Output
+    where
+        T: ComponentTuple,
+    {
+        T::get_from(self, keys)
+    }
+
+    /// The directory saves are written to, `<data dir>/<organization name>` unless
+    /// another location was set.
+    pub fn get_save_dir(&self) -> Result<PathBuf, SaveFileError> {
+        self.location.resolve(&self.org_name)
+    }
+
+    /// The save slots stored in `get_save_dir`.
+    pub fn slots(&self) -> Result<SaveSlots, SaveFileError> {
+        Ok(SaveSlots::new(self.get_save_dir()?))
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveFileError> {
+        let serialized = self.encode(None)?;
+
+        self.write_file(path.as_ref(), serialized)
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(&self, path: P) -> Result<Self, SaveFileError> {
+        let serialized = self.read_file(path.as_ref())?;
+
+        intact(self.decode(&serialized, None)?)
+    }
+
+    /// Async version of `save_to_file`. The save is encoded on the calling task and written
+    /// on a separate thread, so it works with any async runtime.
+    pub async fn save_to_file_async<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveFileError> {
+        let serialized = self.encode(None)?;
+        let pending = self.pending_write(path.as_ref(), serialized)?;
+
+        blocking::unblock(move || pending.write()).await?;
+
+        Ok(())
+    }
+
+    /// Async version of `load_from_file`. The file is read on a separate thread, so it works
+    /// with any async runtime.
+    pub async fn load_from_file_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Self, SaveFileError> {
+        let new_path = self.get_save_dir()?.join(path);
+
+        let serialized = blocking::unblock(move || std::fs::read(new_path)).await?;
+
+        intact(self.decode(&serialized, None)?)
+    }
+
+    /// Loads a save even if some of its components are damaged. The damaged components are
+    /// left out and listed in the returned report, so players keep the rest of their progress.
+    pub fn recover_from_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Self, RecoveryReport), SaveFileError> {
+        let serialized = self.read_file(path.as_ref())?;
+
+        self.decode(&serialized, None)
+    }
+
+    /// Loads `path`, falling back to its backups from newest to oldest if it is damaged.
+    /// Also returns which backup was used, `None` if `path` itself loaded.
+    ///
+    /// Other errors, like a save from a newer build or a file that can't be read, are
+    /// returned as is so the next save doesn't rotate a good file away.
+    pub fn load_from_file_or_backup<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(Self, Option<usize>), SaveFileError> {
+        let err = match self.load_from_file(path.as_ref()) {
+            Ok(loaded) => return Ok((loaded, None)),
+            Err(err) if err.is_damaged() => err,
+            Err(err) => return Err(err),
         };
 
-        let new_path = data_dir.to_string() + "/" + &self.org_name;
+        let new_path = self.get_save_dir()?.join(path);
+
+        for (i, backup) in backup::existing_backups(&new_path).iter().enumerate() {
+            if let Ok(loaded) = self.load_from_file(backup) {
+                return Ok((loaded, Some(i + 1)));
+            }
+        }
 
-        new_path
+        Err(err)
     }
 
-    pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
-        let serialized = serde_json::to_string(&self)?;
+    /// Replaces `path` with its `n`th backup, `1` being the newest.
+    pub fn restore_backup<P: AsRef<Path>>(&self, path: P, n: usize) -> Result<(), SaveFileError> {
+        let new_path = self.get_save_dir()?.join(path);
 
-        let folder = self.get_save_dir();
+        let backup = std::fs::read(backup::backup_path(&new_path, n))?;
+        atomic::write_atomic(&new_path, &backup)?;
 
-        // if the new path doesn't exist create it
-        create_dir_all(folder.clone())?;
+        Ok(())
+    }
 
-        let new_path = folder + "\\" + path;
+    /// Like `save_to_file`, but encrypts the payload with `cipher`. The header is left
+    /// readable so slots can still be listed.
+    pub fn save_to_file_encrypted<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cipher: &dyn Cipher,
+    ) -> Result<(), SaveFileError> {
+        let serialized = self.encode(Some(cipher))?;
 
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(new_path.clone())?;
+        self.write_file(path.as_ref(), serialized)
+    }
 
-        println!("Opened file: {}", new_path);
+    /// Loads a file written by `save_to_file_encrypted`. A wrong key or a modified file
+    /// returns `SaveFileError::DecryptionFailed`.
+    pub fn load_from_file_encrypted<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cipher: &dyn Cipher,
+    ) -> Result<Self, SaveFileError> {
+        let serialized = self.read_file(path.as_ref())?;
 
-        file.write_all(serialized.as_bytes())?;
+        intact(self.decode(&serialized, Some(cipher))?)
+    }
 
-        //std::fs::write(new_path, serialized)?;
-        println!("Wrote to file");
+    fn write_file(&self, path: &Path, bytes: Vec<u8>) -> Result<(), SaveFileError> {
+        self.pending_write(path, bytes)?.write()?;
 
         Ok(())
     }
 
-    pub fn load_from_file(&self, path: &str) -> Result<Self, std::io::Error> {
-        let new_path = self.get_save_dir() + "/" + path;
+    pub(crate) fn pending_write(
+        &self,
+        path: &Path,
+        bytes: Vec<u8>,
+    ) -> Result<PendingWrite, SaveFileError> {
+        Ok(PendingWrite {
+            path: self.get_save_dir()?.join(path),
+            bytes,
+            backups: self.backups,
+        })
+    }
+
+    fn read_file(&self, path: &Path) -> Result<Vec<u8>, SaveFileError> {
+        let new_path = self.get_save_dir()?.join(path);
+
+        Ok(std::fs::read(new_path)?)
+    }
+
+    /// Encodes the save file into the bytes written to disk, header included.
+    pub(crate) fn encode(&self, cipher: Option<&dyn Cipher>) -> Result<Vec<u8>, SaveFileError> {
+        let created = self.header.as_ref().map(|header| header.created);
+        let mut header = SaveHeader::new(&self.game_version, F::NAME, created);
+
+        let mut payload = F::to_document(self)?;
+
+        if let Some(compression) = &self.compression {
+            payload = compression.compress(&payload)?;
+            header.compression = Some(compression.name().to_string());
+        }
+
+        if let Some(cipher) = cipher {
+            payload = cipher.encrypt(&payload)?;
+            header.encryption = Some(cipher.name().to_string());
+        }
+
+        if let Some(integrity) = &self.integrity {
+            header.integrity = Some(IntegrityBlock {
+                algorithm: integrity.name().to_string(),
+                signature: integrity.sign(&header.signed_bytes(&payload)?)?,
+            });
+        }
+
+        header.write(&payload)
+    }
+
+    /// Decodes bytes read from disk. `self` acts as the loader: its migrations are run on
+    /// the result and its settings carry over to it. Damaged components are dropped and
+    /// reported rather than failing the whole load.
+    pub(crate) fn decode(
+        &self,
+        bytes: &[u8],
+        cipher: Option<&dyn Cipher>,
+    ) -> Result<(Self, RecoveryReport), SaveFileError> {
+        let (header, payload) = SaveHeader::split(bytes)?;
+
+        if let Some(integrity) = &self.integrity {
+            let block = header
+                .as_ref()
+                .and_then(|header| Some((header, header.integrity.as_ref()?)))
+                .filter(|(_, block)| block.algorithm == integrity.name());
+
+            let signed = match block {
+                Some((header, block)) => {
+                    integrity.verify(&header.signed_bytes(payload)?, &block.signature)
+                }
+                None => false,
+            };
+
+            if !signed {
+                return Err(SaveFileError::Tampered);
+            }
+        }
+
+        let mut payload = Cow::Borrowed(payload);
+
+        if let Some(header) = &header {
+            if header.encoding != F::NAME {
+                return Err(SaveFileError::EncodingMismatch {
+                    found: header.encoding.clone(),
+                    expected: F::NAME,
+                });
+            }
+
+            if let Some(name) = &header.encryption {
+                let cipher = cipher
+                    .filter(|cipher| cipher.name() == name)
+                    .ok_or_else(|| SaveFileError::UnsupportedEncryption(name.clone()))?;
+
+                let decrypted = cipher
+                    .decrypt(&payload)
+                    .ok_or(SaveFileError::DecryptionFailed)?;
+                payload = Cow::Owned(decrypted);
+            }
+
+            if let Some(name) = &header.compression {
+                let compression = self
+                    .compression
+                    .clone()
+                    .filter(|compression| compression.name() == name)
+                    .or_else(|| compression::builtin(name))
+                    .ok_or_else(|| SaveFileError::UnsupportedCompression(name.clone()))?;
+
+                let decompressed = compression
+                    .decompress(&payload)
+                    .map_err(SaveFileError::Decompression)?;
+                payload = Cow::Owned(decompressed);
+            }
+        }
+
+        // files without a header predate container versions, and use the first layout
+        let container_version = header.as_ref().map_or(1, |header| header.container_version);
+
+        let (mut deserialized, report) = match container_version {
+            ..=1 => F::from_slice::<LegacySaveFile<F>>(&payload)?.recover(),
+            2 => match F::from_slice::<TableSaveFile<F>>(&payload) {
+                Ok(raw) => raw.recover(),
+                Err(err) => F::salvage(&payload)
+                    .and_then(TableSaveFile::recover_salvaged)
+                    .ok_or(err)?,
+            },
+            _ => match F::from_slice::<RawSaveFile<F>>(&payload) {
+                Ok(raw) => raw.recover(),
+                Err(err) => F::salvage(&payload)
+                    .and_then(RawSaveFile::recover_salvaged)
+                    .ok_or(err)?,
+            },
+        };
+        deserialized.header = header;
+
+        // steps that add components need the upcasters to record their current version
+        deserialized.migrations = self.migrations.clone();
+        self.migrations.run(&mut deserialized, self.version)?;
+        deserialized.location = self.location.clone();
+        deserialized.game_version = self.game_version.clone();
+        deserialized.compression = self.compression.clone();
+        deserialized.integrity = self.integrity.clone();
+        deserialized.backups = self.backups;
+        deserialized.checksums = self.checksums;
+
+        Ok((deserialized, report))
+    }
+
+    // puts back a component read from a file, along with its recorded meta
+    fn restore(&mut self, key: String, value: F::Value, version: u32, type_name: Option<String>) {
+        let meta = ComponentMeta { version, type_name };
+
+        if !meta.is_default() {
+            self.meta.insert(key.clone(), meta);
+        }
+        self.map.insert(key, value);
+    }
+}
+
+/// Fails the load if any component had to be dropped.
+fn intact<F: Format>(
+    (save_file, report): (SaveFile<F>, RecoveryReport),
+) -> Result<SaveFile<F>, SaveFileError> {
+    match report.is_clean() {
+        true => Ok(save_file),
+        false => Err(SaveFileError::Corrupt(report)),
+    }
+}
+
+impl<F: Format> Clone for SaveFile<F> {
+    fn clone(&self) -> Self {
+        SaveFile {
+            map: self.map.clone(),
+            org_name: self.org_name.clone(),
+            version: self.version,
+            meta: self.meta.clone(),
+            migrations: self.migrations.clone(),
+            location: self.location.clone(),
+            game_version: self.game_version.clone(),
+            compression: self.compression.clone(),
+            integrity: self.integrity.clone(),
+            backups: self.backups,
+            checksums: self.checksums,
+            header: self.header.clone(),
+            format: PhantomData,
+        }
+    }
+}
+
+impl<F: Format> Serialize for SaveFile<F> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Document::new(self)?.serialize(serializer)
+    }
+}
+
+impl<F: Format> TryFrom<RawSaveFile<F>> for SaveFile<F> {
+    type Error = FormatError;
 
-        let serialized = std::fs::read_to_string(new_path)?;
+    fn try_from(raw: RawSaveFile<F>) -> Result<Self, Self::Error> {
+        let (save_file, report) = raw.recover();
 
-        let deserialized: SaveFile = serde_json::from_str(&serialized)?;
+        if !report.is_clean() {
+            return Err(report.to_string().into());
+        }
 
-        Ok(deserialized)
+        Ok(save_file)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{fs::create_dir_all, time::Duration};
+
     use rand::Rng;
 
     use super::*;
 
+    // keeps the tests from writing into the real user profile
+    fn in_temp_dir<F: Format>(mut save_file: SaveFile<F>) -> SaveFile<F> {
+        save_file.set_location(SaveLocation::Path(
+            std::env::temp_dir().join("ABC-Save-File-Testing"),
+        ));
+        save_file
+    }
+
     #[test]
     fn test_save_file() {
         let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
@@ -160,54 +903,2017 @@ mod tests {
         save_file.add_component(int_key.clone(), int_value).unwrap();
 
         // This will panic because the types don't match
-        let deserialized_bool: i32 = save_file.get_component(&bool_key).unwrap();
-        let deserialized_int: bool = save_file.get_component(&int_key).unwrap();
+        let _deserialized_bool: i32 = save_file.get_component(&bool_key).unwrap();
+        let _deserialized_int: bool = save_file.get_component(&int_key).unwrap();
     }
 
     #[test]
-    fn test_many_values() {
-        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
-
-        for i in 0..10000 {
-            let key = format!("key {}", i);
-            let value = i;
+    fn test_missing_key_is_an_error() {
+        let save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
 
-            save_file.add_component(key.clone(), value).unwrap();
-
-            let deserialized: i32 = save_file.get_component(&key).unwrap();
+        let result = save_file.get_component::<i32>("not there");
 
-            assert_eq!(value, deserialized);
-        }
+        assert!(matches!(result, Err(SaveFileError::MissingKey(key)) if key == "not there"));
     }
 
     #[test]
-    fn test_saving_to_file() {
+    fn test_defaults_for_missing_components() {
         let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
 
-        let mut key_value_pairs = vec![];
+        save_file.add_component("gold".to_string(), 50u32).unwrap();
 
-        let mut rng = rand::thread_rng();
-        for i in 0..10000 {
-            let key = format!("key {}", i);
-            let value = rng.gen_range(0..10000);
+        assert_eq!(
+            save_file.get_component_or_default::<u32>("gold").unwrap(),
+            50
+        );
+        assert_eq!(
+            save_file.get_component_or_default::<u32>("gems").unwrap(),
+            0
+        );
+        assert_eq!(save_file.get_component_or("gems", 3u32).unwrap(), 3);
+        assert!(save_file.get_component_or("gold", false).is_err());
+        assert!(!save_file.contains_key("gems"));
 
-            save_file.add_component(key.clone(), value).unwrap();
+        assert_eq!(save_file.entry("gold").or_insert(10u32).unwrap(), 50);
+        assert_eq!(
+            save_file
+                .entry("difficulty")
+                .or_insert_with(|| "normal".to_string())
+                .unwrap(),
+            "normal"
+        );
+        assert_eq!(save_file.entry("deaths").or_default::<u32>().unwrap(), 0);
 
-            key_value_pairs.push((key, value));
+        let difficulty: String = save_file.get_component("difficulty").unwrap();
+        assert_eq!(difficulty, "normal");
+        assert!(save_file.get_component::<u64>("deaths").is_err());
+    }
+
+    #[test]
+    fn test_update_component_in_place() {
+        #[derive(Serialize, Deserialize)]
+        struct Wallet {
+            gold: u32,
         }
 
-        let path = "save_file.json";
-        println!("Saving to file: {}", save_file.get_save_dir());
-        save_file.save_to_file(path).unwrap();
+        struct Unencodable;
 
-        let loaded_save_file = SaveFile::new("ABC-Save-File-Testing".to_string())
-            .load_from_file(path)
-            .unwrap();
+        impl Serialize for Unencodable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("can't be saved"))
+            }
+        }
 
-        for (key, value) in key_value_pairs {
-            let deserialized: i32 = loaded_save_file.get_component(&key).unwrap();
+        impl<'de> Deserialize<'de> for Unencodable {
+            fn deserialize<D: serde::Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+                Ok(Unencodable)
+            }
+        }
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        save_file
+            .add_component("wallet".to_string(), Wallet { gold: 5 })
+            .unwrap();
+
+        let gold = save_file
+            .update_component("wallet", |wallet: &mut Wallet| {
+                wallet.gold += 10;
+                wallet.gold
+            })
+            .unwrap();
+        assert_eq!(gold, 15);
+        assert_eq!(
+            save_file.get_component::<Wallet>("wallet").unwrap().gold,
+            15
+        );
+
+        assert!(matches!(
+            save_file.update_component("purse", |wallet: &mut Wallet| wallet.gold += 1),
+            Err(SaveFileError::MissingKey(_))
+        ));
+
+        // a failed encode keeps the previous value
+        save_file.meta.get_mut("wallet").unwrap().type_name = None;
+        assert!(save_file
+            .update_component("wallet", |_: &mut Unencodable| ())
+            .is_err());
+        assert_eq!(
+            save_file.get_component::<Wallet>("wallet").unwrap().gold,
+            15
+        );
+    }
+
+    #[test]
+    fn test_mismatched_type_is_an_error() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+
+        save_file
+            .add_component("boolean value".to_string(), true)
+            .unwrap();
+
+        match save_file.get_component::<i32>("boolean value") {
+            Err(SaveFileError::TypeMismatch {
+                key,
+                stored,
+                requested,
+                source,
+            }) => {
+                assert_eq!(key, "boolean value");
+                assert_eq!(stored.as_deref(), Some("bool"));
+                assert_eq!(requested, "i32");
+                assert!(source.is_none());
+            }
+            other => panic!("expected a type mismatch, got {:?}", other),
+        }
+
+        // compatible encodings are still caught by the stored type
+        save_file.add_component("small".to_string(), 7u8).unwrap();
+        assert!(matches!(
+            save_file.get_component::<u32>("small"),
+            Err(SaveFileError::TypeMismatch { .. })
+        ));
+        assert_eq!(save_file.get_component::<u8>("small").unwrap(), 7);
+
+        // module paths and references don't count
+        save_file
+            .add_component("name".to_string(), vec![Some("hero")])
+            .unwrap();
+        assert_eq!(
+            save_file
+                .get_component::<Vec<Option<String>>>("name")
+                .unwrap(),
+            vec![Some("hero".to_string())]
+        );
+        assert!(save_file.get_component::<serde_json::Value>("name").is_ok());
+
+        // components from saves without type tags are decoded as before
+        save_file.meta.get_mut("small").unwrap().type_name = None;
+        assert_eq!(save_file.get_component::<u32>("small").unwrap(), 7);
+    }
+
+    #[test]
+    fn test_compatible_types_are_not_a_mismatch() {
+        use std::collections::{BTreeMap, HashMap, VecDeque};
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        save_file
+            .add_component("bytes".to_string(), vec![1u8, 2, 3])
+            .unwrap();
+        save_file
+            .add_component("scores".to_string(), HashMap::from([("hero", 3u32)]))
+            .unwrap();
+
+        let loaded: SaveFile = Json::from_slice(&Json::to_vec(&save_file).unwrap()).unwrap();
+        assert_eq!(
+            loaded.get_component::<Option<Vec<u8>>>("bytes").unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            loaded.get_component::<VecDeque<u8>>("bytes").unwrap(),
+            VecDeque::from([1, 2, 3])
+        );
+        assert_eq!(
+            loaded
+                .get_component::<BTreeMap<String, u32>>("scores")
+                .unwrap(),
+            BTreeMap::from([("hero".to_string(), 3)])
+        );
+
+        // a different item or collection kind is still caught
+        assert!(matches!(
+            loaded.get_component::<Vec<u16>>("bytes"),
+            Err(SaveFileError::TypeMismatch { source: None, .. })
+        ));
+        assert!(matches!(
+            loaded.get_component::<BTreeMap<String, u32>>("bytes"),
+            Err(SaveFileError::TypeMismatch { source: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_type_names_are_stored_once() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        for i in 0..1000 {
+            save_file.add_component(format!("gold {}", i), i).unwrap();
+            save_file
+                .add_component(format!("names/{}", i), format!("hero {}", i))
+                .unwrap();
+        }
+        save_file.add_component("flag".to_string(), true).unwrap();
+
+        let serialized = Json::to_vec(&save_file).unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&serialized).unwrap();
+        assert_eq!(
+            document["types"],
+            serde_json::json!(["bool", "i32", "String"])
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&serialized).matches("i32").count(),
+            1
+        );
+
+        let loaded: SaveFile = Json::from_slice(&serialized).unwrap();
+        assert_eq!(loaded.get_component::<i32>("gold 7").unwrap(), 7);
+        assert_eq!(loaded.get_component::<String>("names/7").unwrap(), "hero 7");
+        assert!(matches!(
+            loaded.get_component::<String>("gold 7"),
+            Err(SaveFileError::TypeMismatch { stored: Some(stored), .. }) if stored == "i32"
+        ));
+    }
+
+    #[test]
+    fn test_get_components_tuple() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Inventory {
+            gold: u32,
+        }
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+
+        save_file.add_component("health".to_string(), 100).unwrap();
+        save_file
+            .add_component("name".to_string(), "Hero".to_string())
+            .unwrap();
+
+        // "inventory" was added in a later version of the game, "name" is read as the wrong type
+        let (health, name, inventory, bad_name) = save_file
+            .get_components::<(i32, String, Inventory, u64)>([
+                "health",
+                "name",
+                "inventory",
+                "name",
+            ]);
+
+        assert_eq!(health, Some(100));
+        assert_eq!(name, Some("Hero".to_string()));
+        assert_eq!(inventory, None);
+        assert_eq!(bad_name, None);
+    }
+
+    #[test]
+    fn test_many_values() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+
+        for i in 0..10000 {
+            let key = format!("key {}", i);
+            let value = i;
+
+            save_file.add_component(key.clone(), value).unwrap();
+
+            let deserialized: i32 = save_file.get_component(&key).unwrap();
+
+            assert_eq!(value, deserialized);
+        }
+    }
+
+    #[test]
+    fn test_saving_to_file() {
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        let mut key_value_pairs = vec![];
+
+        let mut rng = rand::thread_rng();
+        for i in 0..10000 {
+            let key = format!("key {}", i);
+            let value = rng.gen_range(0..10000);
+
+            save_file.add_component(key.clone(), value).unwrap();
+
+            key_value_pairs.push((key, value));
+        }
+
+        let path = "save_file.json";
+        println!(
+            "Saving to file: {}",
+            save_file.get_save_dir().unwrap().display()
+        );
+        save_file.save_to_file(path).unwrap();
+
+        let loaded_save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()))
+            .load_from_file(path)
+            .unwrap();
+
+        for (key, value) in key_value_pairs {
+            let deserialized: i32 = loaded_save_file.get_component(&key).unwrap();
 
             assert_eq!(value, deserialized);
         }
     }
+
+    #[test]
+    fn test_metadata_size() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        let mut components = serde_json::Map::new();
+        for i in 0..10000 {
+            save_file.add_component(format!("key {}", i), i).unwrap();
+            components.insert(format!("key {}", i), i.into());
+        }
+        save_file.add_component("a/b".to_string(), 1u8).unwrap();
+        components.insert("a/".to_string(), serde_json::json!({ "b": 1 }));
+
+        // the layout from before meta was stored, components and nothing else
+        let baseline = serde_json::to_vec(&serde_json::json!({
+            "components": components,
+            "org_name": "ABC-Save-File-Testing",
+            "version": 0,
+        }))
+        .unwrap()
+        .len();
+
+        // the type costs four bytes per component, a version left at zero nothing, and
+        // every component starts a new line so a damaged one can be skipped
+        save_file.set_checksums(false);
+        let without_checksums = Json::to_document(&save_file).unwrap().len();
+        assert!(
+            without_checksums <= baseline + 10001 * 5 + 96,
+            "{} bytes over",
+            without_checksums - baseline
+        );
+
+        // checksums cost at most eleven bytes per component, a comma and ten digits
+        save_file.set_checksums(true);
+        let with_checksums = Json::to_document(&save_file).unwrap().len();
+        let overhead = with_checksums - without_checksums;
+        assert!(overhead <= 10001 * 11 + 32, "{} bytes", overhead);
+
+        let loaded: SaveFile = Json::from_slice(&Json::to_document(&save_file).unwrap()).unwrap();
+        assert_eq!(loaded.get_component::<i32>("key 9999").unwrap(), 9999);
+        assert_eq!(loaded.get_component::<u8>("a/b").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_saving_floats() {
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        let mut rng = rand::thread_rng();
+        let doubles: Vec<f64> = (0..2000).map(|_| rng.gen()).collect();
+        let singles: Vec<f32> = (0..2000).map(|_| rng.gen::<f32>() * 1000.0).collect();
+
+        for (i, value) in doubles.iter().enumerate() {
+            save_file
+                .add_component(format!("double {}", i), *value)
+                .unwrap();
+        }
+        for (i, value) in singles.iter().enumerate() {
+            save_file
+                .add_component(format!("single {}", i), *value)
+                .unwrap();
+        }
+        save_file
+            .add_component("position".to_string(), (0.1f64, -2.5e-300f64, f64::MAX))
+            .unwrap();
+
+        let path = "float_test.json";
+        save_file.save_to_file(path).unwrap();
+
+        let loaded = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()))
+            .load_from_file(path)
+            .unwrap();
+
+        for (i, value) in doubles.iter().enumerate() {
+            let loaded: f64 = loaded.get_component(&format!("double {}", i)).unwrap();
+            assert_eq!(loaded.to_bits(), value.to_bits());
+        }
+        for (i, value) in singles.iter().enumerate() {
+            let loaded: f32 = loaded.get_component(&format!("single {}", i)).unwrap();
+            assert_eq!(loaded.to_bits(), value.to_bits());
+        }
+        assert_eq!(
+            loaded.get_component::<(f64, f64, f64)>("position").unwrap(),
+            (0.1, -2.5e-300, f64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_migrations_run_on_load() {
+        fn rename_hp(_: u32, save_file: &mut SaveFile) {
+            let hp: i32 = save_file.get_component("hp").unwrap();
+            save_file.add_component("health".to_string(), hp).unwrap();
+        }
+
+        fn double_health(_: u32, save_file: &mut SaveFile) {
+            let health: i32 = save_file.get_component("health").unwrap();
+            save_file
+                .add_component("health".to_string(), health * 2)
+                .unwrap();
+        }
+
+        let path = "migration_test.json";
+
+        let mut old_save = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        old_save.add_component("hp".to_string(), 50).unwrap();
+        old_save.save_to_file(path).unwrap();
+
+        let mut migrations = MigrationRegistry::new();
+        migrations.register(0, rename_hp);
+        migrations.register(2, double_health);
+
+        let mut loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        loader.set_version(3);
+        loader.set_migrations(migrations);
+
+        let loaded = loader.load_from_file(path).unwrap();
+
+        assert_eq!(loaded.version(), 3);
+        assert_eq!(loaded.get_component::<i32>("health").unwrap(), 100);
+
+        // a save from a newer build is rejected
+        loaded.save_to_file(path).unwrap();
+        loader.set_version(1);
+        let result = loader.load_from_file(path);
+        assert!(matches!(
+            result,
+            Err(SaveFileError::UnsupportedVersion {
+                found: 3,
+                supported: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_component_upcasters() {
+        #[derive(Serialize, Deserialize)]
+        struct InventoryV1 {
+            gold: u32,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct InventoryV2 {
+            gold: u32,
+            items: Vec<String>,
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct InventoryV3 {
+            gold: u64,
+            items: Vec<String>,
+            capacity: u32,
+        }
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        save_file
+            .add_component("inventory".to_string(), InventoryV1 { gold: 10 })
+            .unwrap();
+        assert_eq!(save_file.component_version("inventory"), 0);
+
+        let mut migrations = MigrationRegistry::new();
+        migrations.register_upcaster("inventory", 0, |old: InventoryV1| InventoryV2 {
+            gold: old.gold,
+            items: vec![],
+        });
+        migrations.register_upcaster("inventory", 1, |old: InventoryV2| InventoryV3 {
+            gold: old.gold as u64,
+            items: old.items,
+            capacity: 20,
+        });
+        save_file.set_migrations(migrations);
+
+        let inventory: InventoryV3 = save_file.get_component_versioned("inventory").unwrap();
+        assert_eq!(
+            inventory,
+            InventoryV3 {
+                gold: 10,
+                items: vec![],
+                capacity: 20
+            }
+        );
+
+        // new data is written with the current version and isn't upcast again
+        save_file
+            .add_component("inventory".to_string(), inventory)
+            .unwrap();
+        assert_eq!(save_file.component_version("inventory"), 2);
+        let inventory: InventoryV3 = save_file.get_component_versioned("inventory").unwrap();
+        assert_eq!(inventory.capacity, 20);
+    }
+
+    #[test]
+    fn test_migration_steps_write_current_component_versions() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Inventory {
+            gold: u32,
+        }
+
+        fn move_gold(_: u32, save_file: &mut SaveFile) {
+            let gold: u32 = save_file.get_component("gold").unwrap();
+            save_file
+                .add_component("inventory".to_string(), Inventory { gold })
+                .unwrap();
+        }
+
+        let path = "migration_component_version_test.json";
+
+        let mut old_save = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        old_save.add_component("gold".to_string(), 30u32).unwrap();
+        old_save.save_to_file(path).unwrap();
+
+        let mut migrations = MigrationRegistry::new();
+        migrations.register(0, move_gold);
+        // inventories used to be a bare amount of gold
+        migrations.register_upcaster("inventory", 0, |gold: u32| Inventory { gold });
+
+        let mut loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        loader.set_version(1);
+        loader.set_migrations(migrations);
+
+        let loaded = loader.load_from_file(path).unwrap();
+
+        assert_eq!(loaded.component_version("inventory"), 1);
+        assert_eq!(
+            loaded
+                .get_component_versioned::<Inventory>("inventory")
+                .unwrap(),
+            Inventory { gold: 30 }
+        );
+    }
+
+    #[test]
+    fn test_custom_format() {
+        struct PrettyJson;
+
+        impl Format for PrettyJson {
+            const NAME: &'static str = "pretty json";
+
+            type Value = serde_json::Value;
+
+            fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+            where
+                T: Serialize + ?Sized,
+            {
+                Json::to_value(value)
+            }
+
+            fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+            where
+                T: DeserializeOwned,
+            {
+                Json::from_value(value)
+            }
+
+            fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+            where
+                T: Serialize + ?Sized,
+            {
+                Ok(serde_json::to_vec_pretty(value)?)
+            }
+
+            fn from_slice<T>(bytes: &[u8]) -> Result<T, FormatError>
+            where
+                T: DeserializeOwned,
+            {
+                Ok(serde_json::from_slice(bytes)?)
+            }
+        }
+
+        let mut save_file = in_temp_dir(SaveFile::<PrettyJson>::with_format(
+            "ABC-Save-File-Testing".to_string(),
+        ));
+
+        save_file
+            .add_component("position".to_string(), (1.5, -2.0))
+            .unwrap();
+
+        let path = "custom_format_test.json";
+        save_file.save_to_file(path).unwrap();
+
+        let loaded = in_temp_dir(SaveFile::<PrettyJson>::with_format(
+            "ABC-Save-File-Testing".to_string(),
+        ))
+        .load_from_file(path)
+        .unwrap();
+
+        let position: (f64, f64) = loaded.get_component("position").unwrap();
+        assert_eq!(position, (1.5, -2.0));
+    }
+
+    #[test]
+    fn test_components_are_embedded_as_json() {
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        save_file
+            .add_component("player health".to_string(), 905)
+            .unwrap();
+
+        let path = "embedded_json_test.json";
+        save_file.save_to_file(path).unwrap();
+
+        let contents =
+            std::fs::read_to_string(save_file.get_save_dir().unwrap().join(path)).unwrap();
+        // the value as plain JSON, followed by its type and checksum
+        assert!(contents.contains("\"components\":{\n\"player health\":[905,1,"));
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        save_file.add_component("gold".to_string(), 12).unwrap();
+        save_file
+            .namespace("audio")
+            .add_component("volume", 80)
+            .unwrap();
+
+        let mut levels = save_file.namespace("/levels/");
+        assert_eq!(levels.path(), "levels");
+
+        let mut forest = levels.namespace("forest");
+        forest.add_component("chest", true).unwrap();
+        forest.namespace("cave").add_component("bats", 3).unwrap();
+        assert!(forest.get_component::<bool>("chest").unwrap());
+        assert_eq!(forest.keys().collect::<Vec<_>>(), ["chest"]);
+        assert_eq!(forest.namespaces(), ["cave"]);
+
+        levels
+            .namespace("desert")
+            .add_component("chest", false)
+            .unwrap();
+        assert_eq!(levels.keys().count(), 0);
+        assert_eq!(levels.namespaces(), ["desert", "forest"]);
+
+        assert_eq!(
+            save_file
+                .get_component::<i32>("levels/forest/cave/bats")
+                .unwrap(),
+            3
+        );
+
+        let mut migrations = MigrationRegistry::new();
+        migrations.register_upcaster("levels/forest/cave/bats", 0, |bats: i32| bats);
+        save_file.set_migrations(migrations);
+        save_file
+            .namespace("levels/forest/cave")
+            .add_component("bats", 3)
+            .unwrap();
+
+        // written as a nested tree and flattened again on load, here without checksums so
+        // the entries are predictable
+        let path = "namespaces_test.json";
+        save_file.set_checksums(false);
+        save_file.save_to_file(path).unwrap();
+
+        let contents =
+            std::fs::read_to_string(save_file.get_save_dir().unwrap().join(path)).unwrap();
+        assert!(contents.contains(
+            r#""components":{
+"gold":[12,1],
+"audio/":{
+"volume":[80,1]
+},
+"levels/":{
+"desert/":{
+"chest":[false,2]
+},
+"forest/":{
+"chest":[true,2],
+"cave/":{
+"bats":[3,1,null,1]
+}
+}
+}
+}"#
+        ));
+        // with the meta in each entry rather than repeating the full keys
+        assert!(!contents.contains("levels/forest"));
+
+        let mut loaded = save_file.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("audio/volume").unwrap(), 80);
+        assert_eq!(
+            loaded
+                .get_component::<i32>("levels/forest/cave/bats")
+                .unwrap(),
+            3
+        );
+        assert_eq!(loaded.component_version("levels/forest/cave/bats"), 1);
+
+        loaded.namespace("levels/forest").remove();
+        assert!(!loaded.contains_key("levels/forest/chest"));
+        assert!(!loaded.contains_key("levels/forest/cave/bats"));
+        assert!(loaded.contains_key("levels/desert/chest"));
+        assert_eq!(loaded.len(), 3);
+    }
+
+    #[cfg(any(
+        feature = "postcard",
+        feature = "bincode",
+        feature = "msgpack",
+        feature = "cbor"
+    ))]
+    fn check_binary_format<F: Format + std::fmt::Debug>() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+        struct Player {
+            name: String,
+            position: (f32, f32),
+            items: Vec<String>,
+            pet: Option<String>,
+        }
+
+        let player = Player {
+            name: "Hero".to_string(),
+            position: (1.5, -0.25),
+            items: vec!["sword".to_string(), "shield".to_string()],
+            pet: None,
+        };
+
+        let mut migrations = MigrationRegistry::<F>::new();
+        migrations.register_upcaster("player", 0, |player: Player| player);
+
+        let mut save_file = in_temp_dir(SaveFile::<F>::with_format(
+            "ABC-Save-File-Testing".to_string(),
+        ));
+        save_file.set_migrations(migrations);
+        save_file.set_version(3);
+        save_file
+            .add_component("player".to_string(), player.clone())
+            .unwrap();
+        save_file
+            .namespace("levels/forest")
+            .add_component("chest", true)
+            .unwrap();
+        save_file.add_component("gold".to_string(), -12i64).unwrap();
+
+        let path = format!("{}_test.bin", F::NAME);
+        let path = path.as_str();
+        save_file.save_to_file(path).unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        let header = SaveHeader::read(&full_path).unwrap().unwrap();
+        assert_eq!(header.encoding, F::NAME);
+
+        let loaded = save_file.load_from_file(path).unwrap();
+        assert_eq!(loaded.version(), 3);
+        assert_eq!(loaded.get_component::<Player>("player").unwrap(), player);
+        assert_eq!(loaded.component_version("player"), 1);
+        assert!(loaded.get_component::<bool>("levels/forest/chest").unwrap());
+        assert_eq!(loaded.get_component::<i64>("gold").unwrap(), -12);
+        assert!(matches!(
+            loaded.get_component::<String>("gold"),
+            Err(SaveFileError::TypeMismatch { .. })
+        ));
+
+        // the checksums still catch damage without any field names to go by
+        let mut bytes = std::fs::read(&full_path).unwrap();
+        let name = bytes
+            .windows(4)
+            .rposition(|window| window == b"Hero")
+            .unwrap();
+        bytes[name] = b'Z';
+        std::fs::write(&full_path, bytes).unwrap();
+
+        match save_file.load_from_file(path) {
+            Err(SaveFileError::Corrupt(report)) => assert_eq!(report.corrupt, ["player"]),
+            other => panic!("expected a corrupt save, got {:?}", other),
+        }
+
+        // and every field is read by position whether or not checksums are written
+        save_file.set_checksums(false);
+        save_file.save_to_file(path).unwrap();
+        let loaded = save_file.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<Player>("player").unwrap(), player);
+
+        // a JSON loader reports the encoding rather than misreading the bytes
+        let json_loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        assert!(matches!(
+            json_loader.load_from_file(path),
+            Err(SaveFileError::EncodingMismatch { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "postcard")]
+    fn test_postcard_save_files() {
+        check_binary_format::<Postcard>();
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn test_bincode_save_files() {
+        check_binary_format::<Bincode>();
+    }
+
+    #[test]
+    #[cfg(feature = "msgpack")]
+    fn test_msgpack_save_files() {
+        check_binary_format::<MsgPack>();
+    }
+
+    #[test]
+    #[cfg(feature = "cbor")]
+    fn test_cbor_save_files() {
+        check_binary_format::<Cbor>();
+    }
+
+    #[test]
+    fn test_loading_container_v1_payloads() {
+        let save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        // the self-describing layout written before the positional one, meta keyed by component
+        let payload = r#"{"components":{"gold":12,"levels/":{"chest":true}},"org_name":"ABC-Save-File-Testing","version":0,"meta":{"gold":{"checksum":1330857165,"type":"i32"},"levels/chest":{"version":1}}}"#;
+        let header = r#"{"container_version":1,"created":0,"modified":0,"game_version":"","encoding":"json"}"#;
+
+        let path = "container_v1_test.json";
+        let save_dir = save_file.get_save_dir().unwrap();
+        create_dir_all(&save_dir).unwrap();
+        std::fs::write(
+            save_dir.join(path),
+            [&MAGIC[..], header.as_bytes(), b"\n", payload.as_bytes()].concat(),
+        )
+        .unwrap();
+
+        let loaded = save_file.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("gold").unwrap(), 12);
+        assert!(loaded.get_component::<String>("gold").is_err());
+        assert_eq!(loaded.component_version("levels/chest"), 1);
+
+        let damaged = payload.replace(r#""gold":12"#, r#""gold":13"#);
+        std::fs::write(
+            save_dir.join(path),
+            [&MAGIC[..], header.as_bytes(), b"\n", damaged.as_bytes()].concat(),
+        )
+        .unwrap();
+        assert!(matches!(
+            save_file.load_from_file(path),
+            Err(SaveFileError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn test_loading_container_v2_payloads() {
+        let save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        // the positional layout with the versions, types and checksums in tables after the tree
+        let payload = "{\"components\":{\n\"gold\":12,\n\"name\":\"Hero\",\n\"levels/\":{\n\"chest\":true\n}\n},\"org_name\":\"ABC-Save-File-Testing\",\"version\":0,\"versions\":\"AgABAQ==\",\"types\":[\"i32\",\"String\",\"bool\"],\"type_ids\":\"AQEBAgED\",\"checksums\":\"T1NEzf+tm5X9/EyN\"}";
+        let header = r#"{"container_version":2,"created":0,"modified":0,"game_version":"","encoding":"json"}"#;
+
+        let path = "container_v2_test.json";
+        let save_dir = save_file.get_save_dir().unwrap();
+        create_dir_all(&save_dir).unwrap();
+        std::fs::write(
+            save_dir.join(path),
+            [&MAGIC[..], header.as_bytes(), b"\n", payload.as_bytes()].concat(),
+        )
+        .unwrap();
+
+        let loaded = save_file.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("gold").unwrap(), 12);
+        assert_eq!(loaded.get_component::<String>("name").unwrap(), "Hero");
+        assert!(loaded.get_component::<String>("gold").is_err());
+        assert_eq!(loaded.component_version("levels/chest"), 1);
+
+        let damaged = payload.replace(r#""gold":12"#, r#""gold":13"#);
+        std::fs::write(
+            save_dir.join(path),
+            [&MAGIC[..], header.as_bytes(), b"\n", damaged.as_bytes()].concat(),
+        )
+        .unwrap();
+        assert!(matches!(
+            save_file.load_from_file(path),
+            Err(SaveFileError::Corrupt(report)) if report.corrupt == ["gold"]
+        ));
+    }
+
+    #[test]
+    fn test_loading_legacy_byte_arrays() {
+        let save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        // "905" and "true" encoded as JSON bytes, the layout written by older versions
+        let legacy = r#"{"map":{"key 1086":[57,48,53],"flag":[116,114,117,101]},"org_name":"ABC-Save-File-Testing"}"#;
+
+        let path = "legacy_layout_test.json";
+        let save_dir = save_file.get_save_dir().unwrap();
+        create_dir_all(&save_dir).unwrap();
+        std::fs::write(save_dir.join(path), legacy).unwrap();
+
+        let loaded = save_file.load_from_file(path).unwrap();
+
+        assert_eq!(loaded.get_component::<i32>("key 1086").unwrap(), 905);
+        assert!(loaded.get_component::<bool>("flag").unwrap());
+    }
+
+    #[test]
+    fn test_saving_over_a_larger_save() {
+        let path = "overwrite_test.json";
+
+        let mut large_save = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        for i in 0..100 {
+            large_save.add_component(format!("key {}", i), i).unwrap();
+        }
+        large_save.save_to_file(path).unwrap();
+
+        let mut small_save = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        small_save.add_component("key 0".to_string(), 7).unwrap();
+        small_save.save_to_file(path).unwrap();
+
+        let loaded = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()))
+            .load_from_file(path)
+            .unwrap();
+
+        assert_eq!(loaded.get_component::<i32>("key 0").unwrap(), 7);
+        assert!(loaded.get_component::<i32>("key 1").is_err());
+
+        let target = small_save.get_save_dir().unwrap().join(path);
+        assert!(target.exists());
+        assert!(!atomic::temp_path(&target).exists());
+    }
+
+    #[test]
+    fn test_saving_into_a_subdirectory() {
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.add_component("level".to_string(), 3).unwrap();
+
+        let path = Path::new("profiles").join("player one").join("save.json");
+        save_file.save_to_file(&path).unwrap();
+
+        assert!(save_file.get_save_dir().unwrap().join(&path).is_file());
+
+        let loaded = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()))
+            .load_from_file(&path)
+            .unwrap();
+        assert_eq!(loaded.get_component::<i32>("level").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_save_locations() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+
+        if let Some(data_dir) = dirs::data_dir() {
+            assert_eq!(
+                save_file.get_save_dir().unwrap(),
+                data_dir.join("ABC-Save-File-Testing")
+            );
+        }
+
+        let explicit = std::env::temp_dir().join("explicit save dir");
+        save_file.set_location(SaveLocation::Path(explicit.clone()));
+        assert_eq!(save_file.get_save_dir().unwrap(), explicit);
+
+        let exe_dir = std::env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        save_file.set_location(SaveLocation::ExecutableDir);
+        assert_eq!(
+            save_file.get_save_dir().unwrap(),
+            exe_dir.join("ABC-Save-File-Testing")
+        );
+
+        let var = "ABC_SAVE_FILES_TEST_LOCATION_OVERRIDE";
+        save_file.set_location(SaveLocation::env(var, SaveLocation::Path(explicit.clone())));
+        assert_eq!(save_file.get_save_dir().unwrap(), explicit);
+
+        std::env::set_var(var, "/tmp/overridden");
+        assert_eq!(
+            save_file.get_save_dir().unwrap(),
+            PathBuf::from("/tmp/overridden")
+        );
+        std::env::remove_var(var);
+    }
+
+    #[test]
+    fn test_save_slots() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        save_file.set_location(SaveLocation::Path(
+            std::env::temp_dir().join("ABC-Save-File-Testing-Slots"),
+        ));
+
+        let slots = save_file.slots().unwrap();
+        for slot in slots.list().unwrap() {
+            slots.delete(&slot.name).unwrap();
+        }
+        assert!(slots.most_recent().unwrap().is_none());
+
+        save_file.add_component("level".to_string(), 1).unwrap();
+        slots.create("Slot 1", &save_file).unwrap();
+        assert!(matches!(
+            slots.create("Slot 1", &save_file),
+            Err(SaveFileError::SlotExists(_))
+        ));
+
+        save_file.add_component("level".to_string(), 2).unwrap();
+        slots.save("Slot 2", &save_file).unwrap();
+
+        // the header's save time decides, even against a newer file on disk
+        let slot_1 = slots.path("Slot 1").unwrap();
+        let bytes = std::fs::read(&slot_1).unwrap();
+        let (header, payload) = SaveHeader::split(&bytes).unwrap();
+        let mut header = header.unwrap();
+        header.modified += 60;
+        std::fs::write(&slot_1, header.write(payload).unwrap()).unwrap();
+
+        let names: Vec<String> = slots
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|slot| slot.name)
+            .collect();
+        assert_eq!(names, ["Slot 1", "Slot 2"]);
+        assert_eq!(slots.most_recent().unwrap().unwrap().name, "Slot 1");
+
+        slots.copy("Slot 1", "Slot 3").unwrap();
+        slots.rename("Slot 2", "Autosave").unwrap();
+        slots.delete("Slot 1").unwrap();
+
+        let names: Vec<String> = slots
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|slot| slot.name)
+            .collect();
+        assert_eq!(names, ["Autosave", "Slot 3"]);
+
+        let loaded = slots.load("Slot 3", &save_file).unwrap();
+        assert_eq!(loaded.get_component::<i32>("level").unwrap(), 1);
+        let loaded = slots.load("Autosave", &save_file).unwrap();
+        assert_eq!(loaded.get_component::<i32>("level").unwrap(), 2);
+
+        assert!(matches!(
+            slots.load("Slot 1", &save_file),
+            Err(SaveFileError::SlotNotFound(_))
+        ));
+        assert!(matches!(
+            slots.path("../escape"),
+            Err(SaveFileError::InvalidSlotName(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_file_header() {
+        let path = "header_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_game_version("1.4.2");
+        save_file.add_component("level".to_string(), 4).unwrap();
+        save_file.save_to_file(path).unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        assert!(std::fs::read(&full_path).unwrap().starts_with(MAGIC));
+
+        let loaded = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()))
+            .load_from_file(path)
+            .unwrap();
+        let header = loaded.header().unwrap();
+        assert_eq!(header.container_version, CONTAINER_VERSION);
+        assert_eq!(header.game_version, "1.4.2");
+        assert_eq!(header.encoding, "json");
+        assert!(header.created <= header.modified);
+        assert_eq!(SaveHeader::read(&full_path).unwrap().as_ref(), Some(header));
+
+        // saving again keeps the creation time
+        loaded.save_to_file(path).unwrap();
+        let resaved = SaveHeader::read(&full_path).unwrap().unwrap();
+        assert_eq!(resaved.created, header.created);
+    }
+
+    #[test]
+    fn test_rejecting_foreign_and_newer_files() {
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        let save_dir = loader.get_save_dir().unwrap();
+        create_dir_all(&save_dir).unwrap();
+
+        std::fs::write(save_dir.join("foreign.png"), b"\x89PNG\r\n").unwrap();
+        assert!(matches!(
+            loader.load_from_file("foreign.png"),
+            Err(SaveFileError::NotASaveFile)
+        ));
+
+        let mut newer = MAGIC.to_vec();
+        newer.extend_from_slice(
+            br#"{"container_version":99,"created":0,"modified":0,"game_version":"","encoding":"json"}"#,
+        );
+        newer.extend_from_slice(b"\n{}");
+        std::fs::write(save_dir.join("newer.json"), newer).unwrap();
+        assert!(matches!(
+            loader.load_from_file("newer.json"),
+            Err(SaveFileError::UnsupportedContainerVersion {
+                found: 99,
+                supported: CONTAINER_VERSION
+            })
+        ));
+    }
+
+    // a simple run length encoding, standing in for a codec that isn't built in
+    #[derive(Debug)]
+    struct RunLength;
+
+    impl Compression for RunLength {
+        fn name(&self) -> &str {
+            "rle"
+        }
+
+        fn compress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+            let mut compressed = vec![];
+            for chunk in bytes.chunk_by(|a, b| a == b) {
+                for run in chunk.chunks(u8::MAX as usize) {
+                    compressed.extend_from_slice(&[run.len() as u8, run[0]]);
+                }
+            }
+            Ok(compressed)
+        }
+
+        fn decompress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+            if !bytes.len().is_multiple_of(2) {
+                return Err(std::io::ErrorKind::InvalidData.into());
+            }
+
+            Ok(bytes
+                .chunks(2)
+                .flat_map(|pair| std::iter::repeat_n(pair[1], pair[0] as usize))
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_compressed_saves() {
+        let path = "compression_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_compression(Some(Arc::new(RunLength)));
+        save_file
+            .add_component("fog of war".to_string(), "#".repeat(5000))
+            .unwrap();
+        save_file.save_to_file(path).unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        assert!(std::fs::metadata(&full_path).unwrap().len() < 5000);
+        let header = SaveHeader::read(&full_path).unwrap().unwrap();
+        assert_eq!(header.compression.as_deref(), Some("rle"));
+
+        let plain_loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        assert!(matches!(
+            plain_loader.load_from_file(path),
+            Err(SaveFileError::UnsupportedCompression(name)) if name == "rle"
+        ));
+
+        let mut loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        loader.set_compression(Some(Arc::new(RunLength)));
+        let loaded = loader.load_from_file(path).unwrap();
+        assert_eq!(
+            loaded.get_component::<String>("fog of war").unwrap(),
+            "#".repeat(5000)
+        );
+    }
+
+    #[cfg(any(feature = "gzip", feature = "lz4", feature = "zstd"))]
+    fn check_builtin_compression(codec: Arc<dyn Compression>) {
+        let path = format!("{}_test.json", codec.name());
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_compression(Some(codec));
+        save_file
+            .add_component("fog of war".to_string(), "#.".repeat(5000))
+            .unwrap();
+        save_file.save_to_file(&path).unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(&path);
+        assert!(std::fs::metadata(&full_path).unwrap().len() < 1000);
+
+        // the codec is picked from the header, whatever the loader is set up with
+        let plain_loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        let mut other_loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        other_loader.set_compression(Some(Arc::new(RunLength)));
+
+        for loader in [plain_loader, other_loader] {
+            let loaded = loader.load_from_file(&path).unwrap();
+            assert_eq!(
+                loaded.get_component::<String>("fog of war").unwrap(),
+                "#.".repeat(5000)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_gzip_saves() {
+        check_builtin_compression(Arc::new(Gzip));
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn test_lz4_saves() {
+        check_builtin_compression(Arc::new(Lz4));
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_zstd_saves() {
+        check_builtin_compression(Arc::new(Zstd));
+    }
+
+    #[test]
+    fn test_encrypted_saves() {
+        // stands in for an AEAD cipher: the first byte acts as the authentication tag
+        struct XorCipher(u8);
+
+        impl Cipher for XorCipher {
+            fn name(&self) -> &str {
+                "xor"
+            }
+
+            fn encrypt(&self, plaintext: &[u8]) -> std::io::Result<Vec<u8>> {
+                let mut ciphertext = vec![self.0];
+                ciphertext.extend(plaintext.iter().map(|byte| byte ^ self.0));
+                Ok(ciphertext)
+            }
+
+            fn decrypt(&self, ciphertext: &[u8]) -> Option<Vec<u8>> {
+                let (&tag, rest) = ciphertext.split_first()?;
+                (tag == self.0).then(|| rest.iter().map(|byte| byte ^ self.0).collect())
+            }
+        }
+
+        let path = "encryption_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file
+            .add_component("gems purchased".to_string(), 1200)
+            .unwrap();
+        save_file
+            .save_to_file_encrypted(path, &XorCipher(0x5a))
+            .unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        let contents = std::fs::read(&full_path).unwrap();
+        assert!(!contents
+            .windows(b"gems purchased".len())
+            .any(|window| window == b"gems purchased"));
+
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        let loaded = loader
+            .load_from_file_encrypted(path, &XorCipher(0x5a))
+            .unwrap();
+        assert_eq!(loaded.get_component::<i32>("gems purchased").unwrap(), 1200);
+
+        assert!(matches!(
+            loader.load_from_file_encrypted(path, &XorCipher(0x11)),
+            Err(SaveFileError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            loader.load_from_file(path),
+            Err(SaveFileError::UnsupportedEncryption(name)) if name == "xor"
+        ));
+    }
+
+    // saves with `cipher`, then loads with it and with `same_key`, and fails to with `wrong_key`
+    #[cfg(any(feature = "aes-gcm", feature = "chacha20poly1305"))]
+    fn check_builtin_cipher(cipher: &dyn Cipher, same_key: &dyn Cipher, wrong_key: &dyn Cipher) {
+        let path = format!("{}_test.json", cipher.name());
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file
+            .add_component("gems purchased".to_string(), 1200)
+            .unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(&path);
+        save_file.save_to_file_encrypted(&path, cipher).unwrap();
+        let first = std::fs::read(&full_path).unwrap();
+        save_file.save_to_file_encrypted(&path, cipher).unwrap();
+        let second = std::fs::read(&full_path).unwrap();
+
+        // every save gets its own nonce
+        assert_ne!(
+            SaveHeader::split(&first).unwrap().1,
+            SaveHeader::split(&second).unwrap().1
+        );
+
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        for cipher in [cipher, same_key] {
+            let loaded = loader.load_from_file_encrypted(&path, cipher).unwrap();
+            assert_eq!(loaded.get_component::<i32>("gems purchased").unwrap(), 1200);
+        }
+
+        assert!(matches!(
+            loader.load_from_file_encrypted(&path, wrong_key),
+            Err(SaveFileError::DecryptionFailed)
+        ));
+    }
+
+    #[cfg(feature = "chacha20poly1305")]
+    #[test]
+    fn test_chacha20poly1305_saves() {
+        check_builtin_cipher(
+            &ChaCha20Poly1305::new([1; 32]),
+            &ChaCha20Poly1305::new([1; 32]),
+            &ChaCha20Poly1305::new([2; 32]),
+        );
+
+        // few rounds, to keep the test fast; the second has a fresh salt, so the key is
+        // derived again from the saved one
+        check_builtin_cipher(
+            &ChaCha20Poly1305::from_passphrase("hunter2", 1000).unwrap(),
+            &ChaCha20Poly1305::from_passphrase("hunter2", 1000).unwrap(),
+            &ChaCha20Poly1305::from_passphrase("hunter3", 1000).unwrap(),
+        );
+
+        // a save can't ask for more rounds than the loader was set up with
+        let path = "cipher_rounds_test.json";
+        let slow = ChaCha20Poly1305::from_passphrase("hunter2", 2000).unwrap();
+        let save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.save_to_file_encrypted(path, &slow).unwrap();
+
+        let fast = ChaCha20Poly1305::from_passphrase("hunter2", 1000).unwrap();
+        assert!(matches!(
+            save_file.load_from_file_encrypted(path, &fast),
+            Err(SaveFileError::DecryptionFailed)
+        ));
+    }
+
+    #[cfg(feature = "aes-gcm")]
+    #[test]
+    fn test_aes_gcm_saves() {
+        check_builtin_cipher(
+            &Aes256Gcm::new([1; 32]),
+            &Aes256Gcm::new([1; 32]),
+            &Aes256Gcm::new([2; 32]),
+        );
+        check_builtin_cipher(
+            &Aes256Gcm::from_passphrase("hunter2", 1000).unwrap(),
+            &Aes256Gcm::from_passphrase("hunter2", 1000).unwrap(),
+            &Aes256Gcm::from_passphrase("hunter3", 1000).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_tamper_detection() {
+        // stands in for an HMAC: a checksum that depends on a secret
+        #[derive(Debug)]
+        struct KeyedSum(u64);
+
+        impl Integrity for KeyedSum {
+            fn name(&self) -> &str {
+                "keyed-sum"
+            }
+
+            fn sign(&self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+                let sum = data.iter().fold(self.0, |sum, &byte| {
+                    sum.wrapping_mul(31).wrapping_add(byte as u64)
+                });
+                Ok(sum.to_le_bytes().to_vec())
+            }
+
+            fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
+                self.sign(data).is_ok_and(|expected| expected == signature)
+            }
+        }
+
+        let path = "integrity_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_integrity(Some(Arc::new(KeyedSum(42))));
+        save_file.set_game_version("1.0");
+        save_file
+            .add_component("high score".to_string(), 900)
+            .unwrap();
+        save_file.save_to_file(path).unwrap();
+
+        let mut loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        loader.set_integrity(Some(Arc::new(KeyedSum(42))));
+        let loaded = loader.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("high score").unwrap(), 900);
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        let signed = std::fs::read_to_string(&full_path).unwrap();
+        let header = loaded.header().unwrap();
+
+        // the header is signed along with the payload
+        let edits = [
+            ("900", "999".to_string()),
+            ("\"1.0\"", "\"2.0\"".to_string()),
+            (
+                &*format!("\"modified\":{}", header.modified),
+                format!("\"modified\":{}", header.modified + 1),
+            ),
+        ];
+        for (from, to) in edits {
+            std::fs::write(&full_path, signed.replacen(from, &to, 1)).unwrap();
+            assert!(matches!(
+                loader.load_from_file(path),
+                Err(SaveFileError::Tampered)
+            ));
+        }
+
+        // unsigned saves are rejected as well
+        let unsigned = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        unsigned.save_to_file(path).unwrap();
+        assert!(matches!(
+            loader.load_from_file(path),
+            Err(SaveFileError::Tampered)
+        ));
+    }
+
+    #[cfg(any(feature = "hmac", feature = "ed25519"))]
+    fn check_builtin_integrity(signer: Arc<dyn Integrity>, verifier: Arc<dyn Integrity>) {
+        let path = format!("{}_test.json", signer.name());
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_integrity(Some(signer));
+        save_file.add_component("gold".to_string(), 50).unwrap();
+        save_file.save_to_file(&path).unwrap();
+
+        let mut loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        loader.set_integrity(Some(verifier));
+        let loaded = loader.load_from_file(&path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("gold").unwrap(), 50);
+
+        let full_path = save_file.get_save_dir().unwrap().join(&path);
+        let edited = std::fs::read_to_string(&full_path)
+            .unwrap()
+            .replace("50", "99");
+        std::fs::write(&full_path, edited).unwrap();
+        assert!(matches!(
+            loader.load_from_file(&path),
+            Err(SaveFileError::Tampered)
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "hmac")]
+    fn test_hmac_saves() {
+        check_builtin_integrity(
+            Arc::new(HmacSha256::new("game secret")),
+            Arc::new(HmacSha256::new("game secret")),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ed25519")]
+    fn test_ed25519_saves() {
+        let server_key = Ed25519::from_seed([3; 32]);
+        let game_key = Ed25519::verifying(server_key.public_key()).unwrap();
+
+        check_builtin_integrity(Arc::new(server_key), Arc::new(game_key.clone()));
+
+        // the public key alone can't sign
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_integrity(Some(Arc::new(game_key)));
+        assert!(save_file.save_to_file("ed25519_test.json").is_err());
+    }
+
+    #[test]
+    fn test_recovering_damaged_components() {
+        let path = "recovery_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.add_component("gold".to_string(), 1234).unwrap();
+        save_file
+            .add_component("name".to_string(), "Hero".to_string())
+            .unwrap();
+        save_file.save_to_file(path).unwrap();
+
+        // flip a digit, the file is still valid JSON but "gold" no longer matches its checksum
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        let damaged = std::fs::read_to_string(&full_path)
+            .unwrap()
+            .replace("1234", "1294");
+        std::fs::write(&full_path, damaged).unwrap();
+
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        match loader.load_from_file(path) {
+            Err(SaveFileError::Corrupt(report)) => assert_eq!(report.corrupt, ["gold"]),
+            other => panic!("expected a corrupt save, got {:?}", other),
+        }
+
+        let (recovered, report) = loader.recover_from_file(path).unwrap();
+        assert_eq!(report.corrupt, ["gold"]);
+        assert!(report.unparsable.is_empty());
+        assert!(recovered.get_component::<i32>("gold").is_err());
+        assert_eq!(recovered.get_component::<String>("name").unwrap(), "Hero");
+
+        // a damaged byte that breaks the JSON only loses the component it's in
+        save_file.save_to_file(path).unwrap();
+        let damaged = std::fs::read_to_string(&full_path)
+            .unwrap()
+            .replace("1234", "12}4");
+        std::fs::write(&full_path, damaged).unwrap();
+
+        match loader.load_from_file(path) {
+            Err(SaveFileError::Corrupt(report)) => assert_eq!(report.unparsable, ["gold"]),
+            other => panic!("expected a corrupt save, got {:?}", other),
+        }
+
+        let (recovered, report) = loader.recover_from_file(path).unwrap();
+        assert_eq!(report.unparsable, ["gold"]);
+        assert!(report.corrupt.is_empty());
+        assert_eq!(recovered.get_component::<String>("name").unwrap(), "Hero");
+
+        // legacy entries can't carry a checksum, but bytes that don't decode are reported
+        let legacy =
+            r#"{"map":{"gold":[57,48,53],"name":[34,72,101]},"org_name":"ABC-Save-File-Testing"}"#;
+        std::fs::write(&full_path, legacy).unwrap();
+
+        let (recovered, report) = loader.recover_from_file(path).unwrap();
+        assert_eq!(report.unparsable, ["name"]);
+        assert_eq!(recovered.get_component::<i32>("gold").unwrap(), 905);
+    }
+
+    #[test]
+    fn test_damage_stays_in_its_entry() {
+        let path = "entry_damage_test.json";
+
+        let mut migrations = MigrationRegistry::new();
+        migrations.register_upcaster("bats", 0, |bats: i32| bats);
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_migrations(migrations);
+        save_file.add_component("bats".to_string(), 3).unwrap();
+        save_file.add_component("gold".to_string(), 1234).unwrap();
+        save_file
+            .add_component("name".to_string(), "Hero".to_string())
+            .unwrap();
+        save_file.save_to_file(path).unwrap();
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        let contents = std::fs::read_to_string(&full_path).unwrap();
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+
+        // changes the last byte of `key`'s entry, the one before its closing bracket
+        let damage = |key: &str, change: fn(u8) -> u8| {
+            let start = contents.find(&format!("\n\"{}\":", key)).unwrap();
+            let at = start + contents[start..].find(']').unwrap() - 1;
+
+            let mut damaged = contents.clone().into_bytes();
+            damaged[at] = change(damaged[at]);
+            std::fs::write(&full_path, damaged).unwrap();
+
+            loader.recover_from_file(path).unwrap()
+        };
+
+        // a string value split over two lines
+        std::fs::write(&full_path, contents.replace("Hero", "H\nro")).unwrap();
+        let (recovered, report) = loader.recover_from_file(path).unwrap();
+        assert_eq!(report.unparsable, ["name"]);
+        assert!(report.corrupt.is_empty());
+        assert_eq!(recovered.get_component::<i32>("gold").unwrap(), 1234);
+        assert_eq!(recovered.get_component::<i32>("bats").unwrap(), 3);
+        assert_eq!(recovered.component_version("bats"), 1);
+
+        // the version, the last field of the entry
+        let (recovered, report) = damage("bats", |_| b'3');
+        assert_eq!(report.corrupt, ["bats"]);
+        assert!(report.unparsable.is_empty());
+        assert_eq!(recovered.len(), 2);
+
+        // the checksum, still a number or not
+        let changes: [fn(u8) -> u8; 2] = [|digit| b'0' + (digit - b'0' + 1) % 10, |_| b'x'];
+        for change in changes {
+            let (recovered, report) = damage("gold", change);
+            assert_eq!(recovered.len(), 2);
+            assert_eq!(recovered.get_component::<String>("name").unwrap(), "Hero");
+            assert_eq!(recovered.component_version("bats"), 1);
+
+            let lost = [report.corrupt, report.unparsable].concat();
+            assert_eq!(lost, ["gold"]);
+        }
+    }
+
+    #[test]
+    fn test_rotating_backups() {
+        let path = "backup_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_backups(2);
+
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        for stale in [1, 2, 3].map(|n| backup::backup_path(&full_path, n)) {
+            let _ = std::fs::remove_file(stale);
+        }
+
+        for level in 1..=4 {
+            save_file.add_component("level".to_string(), level).unwrap();
+            save_file.save_to_file(path).unwrap();
+        }
+
+        // the primary holds level 4, the backups the two saves before it
+        assert!(backup::backup_path(&full_path, 2).is_file());
+        assert!(!backup::backup_path(&full_path, 3).exists());
+
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        let level_in = |backup: Option<usize>| {
+            let file = match backup {
+                Some(n) => backup::backup_path(Path::new(path), n),
+                None => PathBuf::from(path),
+            };
+            loader
+                .load_from_file(file)
+                .unwrap()
+                .get_component::<i32>("level")
+                .unwrap()
+        };
+        assert_eq!(level_in(None), 4);
+        assert_eq!(level_in(Some(1)), 3);
+        assert_eq!(level_in(Some(2)), 2);
+
+        // a damaged primary falls back to the newest backup
+        std::fs::write(&full_path, b"garbage").unwrap();
+        let (loaded, used) = loader.load_from_file_or_backup(path).unwrap();
+        assert_eq!(used, Some(1));
+        assert_eq!(loaded.get_component::<i32>("level").unwrap(), 3);
+
+        loader.restore_backup(path, 2).unwrap();
+        let (loaded, used) = loader.load_from_file_or_backup(path).unwrap();
+        assert_eq!(used, None);
+        assert_eq!(loaded.get_component::<i32>("level").unwrap(), 2);
+
+        // a primary that is intact but can't be loaded is reported, not replaced
+        save_file.set_version(5);
+        save_file.save_to_file(path).unwrap();
+        assert!(matches!(
+            loader.load_from_file_or_backup(path),
+            Err(SaveFileError::UnsupportedVersion { found: 5, .. })
+        ));
+    }
+
+    #[test]
+    fn test_compressed_backup_fallback() {
+        let path = "compressed_backup_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_compression(Some(Arc::new(RunLength)));
+        save_file.set_backups(1);
+
+        for level in 1..=2 {
+            save_file.add_component("level".to_string(), level).unwrap();
+            save_file.save_to_file(path).unwrap();
+        }
+
+        // a payload cut short no longer decompresses
+        let full_path = save_file.get_save_dir().unwrap().join(path);
+        let mut bytes = std::fs::read(&full_path).unwrap();
+        bytes.pop();
+        std::fs::write(&full_path, bytes).unwrap();
+
+        let mut loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        loader.set_compression(Some(Arc::new(RunLength)));
+        assert!(matches!(
+            loader.load_from_file(path),
+            Err(SaveFileError::Decompression(_))
+        ));
+
+        let (loaded, used) = loader.load_from_file_or_backup(path).unwrap();
+        assert_eq!(used, Some(1));
+        assert_eq!(loaded.get_component::<i32>("level").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_autosaver() {
+        let path = "autosave_test.json";
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.add_component("gold".to_string(), 0).unwrap();
+
+        let autosaver = AutoSaver::new(save_file, path, Duration::from_millis(20));
+        assert!(autosaver.last_result().is_none());
+
+        for gold in 1..=10 {
+            autosaver
+                .save_file()
+                .add_component("gold".to_string(), gold)
+                .unwrap();
+            autosaver.request_save();
+        }
+
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        autosaver.flush().unwrap();
+        let loaded = loader.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("gold").unwrap(), 10);
+
+        // a long debounce is cut short by dropping the saver
+        let slow = AutoSaver::new(loaded, path, Duration::from_secs(60 * 60));
+        slow.save_file()
+            .add_component("gold".to_string(), 11)
+            .unwrap();
+        slow.request_save();
+        assert!(slow.shutdown().unwrap().is_ok());
+
+        let loaded = loader.load_from_file(path).unwrap();
+        assert_eq!(loaded.get_component::<i32>("gold").unwrap(), 11);
+    }
+
+    #[test]
+    fn test_autosaver_encodes_without_the_lock() {
+        use std::sync::{
+            mpsc::{channel, Receiver, Sender},
+            Mutex,
+        };
+
+        // holds the worker inside encoding until the test lets it go
+        #[derive(Debug)]
+        struct Gate {
+            entered: Sender<()>,
+            release: Mutex<Receiver<()>>,
+        }
+
+        impl Compression for Gate {
+            fn name(&self) -> &str {
+                "gate"
+            }
+
+            fn compress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+                let _ = self.entered.send(());
+                let _ = self.release.lock().unwrap().recv();
+                Ok(bytes.to_vec())
+            }
+
+            fn decompress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+                Ok(bytes.to_vec())
+            }
+        }
+
+        let (entered, encoding) = channel();
+        let (release, released) = channel();
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file.set_compression(Some(Arc::new(Gate {
+            entered,
+            release: Mutex::new(released),
+        })));
+        save_file.add_component("gold".to_string(), 1).unwrap();
+
+        let autosaver = AutoSaver::new(save_file, "autosave_lock_test.json", Duration::ZERO);
+        autosaver.request_save();
+        encoding.recv().unwrap();
+
+        // the game isn't kept waiting while the snapshot is encoded
+        let (edited, done) = channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                autosaver
+                    .save_file()
+                    .add_component("gold".to_string(), 2)
+                    .unwrap();
+                edited.send(()).unwrap();
+            });
+
+            let result = done.recv_timeout(Duration::from_secs(5));
+            release.send(()).unwrap();
+            assert!(result.is_ok(), "the save file stayed locked while encoding");
+        });
+
+        assert!(autosaver.shutdown().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_async_save_and_load() {
+        use std::{
+            future::Future,
+            pin::pin,
+            sync::Arc,
+            task::{Context, Poll, Wake, Waker},
+            thread::{self, Thread},
+        };
+
+        // a minimal executor, the API doesn't depend on any particular runtime
+        fn block_on<T>(future: impl Future<Output = T>) -> T {
+            struct ThreadWaker(Thread);
+
+            impl Wake for ThreadWaker {
+                fn wake(self: Arc<Self>) {
+                    self.0.unpark();
+                }
+            }
+
+            let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+            let mut context = Context::from_waker(&waker);
+            let mut future = pin!(future);
+
+            loop {
+                match future.as_mut().poll(&mut context) {
+                    Poll::Ready(value) => return value,
+                    Poll::Pending => thread::park(),
+                }
+            }
+        }
+
+        let path = Path::new("async").join("async_test.json");
+
+        let mut save_file = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        save_file
+            .add_component("quest".to_string(), "Find the sword".to_string())
+            .unwrap();
+        block_on(save_file.save_to_file_async(&path)).unwrap();
+
+        let loader = in_temp_dir(SaveFile::new("ABC-Save-File-Testing".to_string()));
+        let loaded = block_on(loader.load_from_file_async(&path)).unwrap();
+        assert_eq!(
+            loaded.get_component::<String>("quest").unwrap(),
+            "Find the sword"
+        );
+
+        let missing = block_on(loader.load_from_file_async("async/missing.json"));
+        assert!(matches!(missing, Err(SaveFileError::Io(_))));
+    }
+
+    #[test]
+    fn test_key_management() {
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        assert!(save_file.is_empty());
+
+        for i in 0..10 {
+            save_file.add_component(format!("key {}", i), i).unwrap();
+        }
+        save_file
+            .add_component("obsolete".to_string(), true)
+            .unwrap();
+
+        assert_eq!(save_file.len(), 11);
+        assert!(save_file.contains_key("obsolete"));
+
+        assert_eq!(
+            save_file.remove_component("obsolete"),
+            Some(serde_json::Value::Bool(true))
+        );
+        assert_eq!(save_file.remove_component("obsolete"), None);
+        assert!(!save_file.contains_key("obsolete"));
+        assert!(!save_file.meta.contains_key("obsolete"));
+
+        let mut keys: Vec<&str> = save_file.keys().collect();
+        keys.sort();
+        assert_eq!(keys.len(), 10);
+        assert_eq!(keys[0], "key 0");
+
+        let total: i64 = save_file
+            .iter()
+            .map(|(_, value)| value.as_i64().unwrap())
+            .sum();
+        assert_eq!(total, 45);
+
+        save_file.retain(|_, value| value.as_i64().unwrap() % 2 == 0);
+        assert_eq!(save_file.len(), 5);
+        assert_eq!(save_file.meta.len(), 5);
+        assert!(save_file.contains_key("key 4"));
+        assert!(!save_file.contains_key("key 5"));
+
+        save_file.clear();
+        assert!(save_file.is_empty());
+        assert!(save_file.meta.is_empty());
+    }
+
+    #[test]
+    fn test_typed_keys() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Position {
+            x: f32,
+            y: f32,
+        }
+
+        const HEALTH: SaveKey<i32> = SaveKey::new("player health");
+        const POSITION: SaveKey<Position> = SaveKey::new("player position");
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+
+        save_file.set(&HEALTH, 100).unwrap();
+        save_file
+            .set(&POSITION, Position { x: 1.0, y: -3.5 })
+            .unwrap();
+
+        assert_eq!(save_file.get(&HEALTH).unwrap(), 100);
+        assert_eq!(
+            save_file.get(&POSITION).unwrap(),
+            Position { x: 1.0, y: -3.5 }
+        );
+
+        // typed keys and string keys refer to the same components
+        assert_eq!(
+            save_file.get_component::<i32>("player health").unwrap(),
+            100
+        );
+        assert!(matches!(
+            save_file.get(&SaveKey::<i32>::new("missing")),
+            Err(SaveFileError::MissingKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_derived_save_components() {
+        use ABC_Save_Files_derive::SaveComponent;
+
+        #[derive(Serialize, Deserialize, SaveComponent, Debug, PartialEq)]
+        #[save_component(key = "player", version = 1)]
+        struct Player {
+            name: String,
+            level: u32,
+        }
+
+        #[derive(Serialize, Deserialize, SaveComponent, Debug, PartialEq, Default)]
+        #[save_component(default)]
+        struct AudioSettings {
+            volume: u8,
+        }
+
+        #[derive(Serialize, Deserialize)]
+        struct PlayerV0 {
+            name: String,
+        }
+
+        assert_eq!(<Player as super::SaveComponent>::KEY, "player");
+        assert_eq!(
+            <AudioSettings as super::SaveComponent>::KEY,
+            "AudioSettings"
+        );
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+
+        // missing components use the default only when the type asks for it
+        assert_eq!(
+            save_file.take::<AudioSettings>().unwrap(),
+            AudioSettings::default()
+        );
+        assert!(matches!(
+            save_file.take::<Player>(),
+            Err(SaveFileError::MissingKey(key)) if key == "player"
+        ));
+
+        let player = Player {
+            name: "Hero".to_string(),
+            level: 7,
+        };
+        save_file.put(&player).unwrap();
+        assert_eq!(save_file.component_version("player"), 1);
+        assert_eq!(save_file.take::<Player>().unwrap(), player);
+
+        // data written before the version bump is upcast on the way out
+        save_file
+            .add_component(
+                "player".to_string(),
+                PlayerV0 {
+                    name: "Old Hero".to_string(),
+                },
+            )
+            .unwrap();
+        let mut migrations = MigrationRegistry::new();
+        migrations.register_upcaster("player", 0, |old: PlayerV0| Player {
+            name: old.name,
+            level: 1,
+        });
+        save_file.set_migrations(migrations);
+
+        assert_eq!(
+            save_file.take::<Player>().unwrap(),
+            Player {
+                name: "Old Hero".to_string(),
+                level: 1
+            }
+        );
+
+        // a type that disagrees with its upcasters can't be written or read
+        #[derive(Serialize, Deserialize, SaveComponent, Debug, PartialEq)]
+        #[save_component(key = "player", version = 2)]
+        struct PlayerV2 {
+            name: String,
+        }
+
+        assert!(matches!(
+            save_file.put(&PlayerV2 {
+                name: "Hero".to_string()
+            }),
+            Err(SaveFileError::UnsupportedComponentVersion {
+                found: 2,
+                supported: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            save_file.take::<PlayerV2>(),
+            Err(SaveFileError::UnsupportedComponentVersion {
+                found: 2,
+                supported: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_component_versions_without_upcasters() {
+        use ABC_Save_Files_derive::SaveComponent;
+
+        #[derive(Serialize, Deserialize, SaveComponent, Debug, PartialEq)]
+        #[save_component(key = "player", version = 2)]
+        struct Player {
+            level: u32,
+        }
+
+        let mut save_file = SaveFile::new("ABC-Save-File-Testing".to_string());
+        save_file.put(&Player { level: 1 }).unwrap();
+
+        save_file
+            .update_component("player", |player: &mut Player| player.level += 1)
+            .unwrap();
+        assert_eq!(save_file.component_version("player"), 2);
+        assert_eq!(
+            save_file
+                .get_component_versioned::<Player>("player")
+                .unwrap(),
+            Player { level: 2 }
+        );
+        assert_eq!(save_file.take::<Player>().unwrap(), Player { level: 2 });
+
+        // older data still needs an upcaster to be read as the newer type
+        save_file.add_component("player".to_string(), 7u32).unwrap();
+        assert!(matches!(
+            save_file.take::<Player>(),
+            Err(SaveFileError::MissingUpcaster {
+                from_version: 0,
+                ..
+            })
+        ));
+    }
 }
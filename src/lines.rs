@@ -0,0 +1,258 @@
+//! The layout [`Json`](crate::Json) writes save files with: the usual compact JSON, except
+//! that every entry of the components tree starts a new line and every level of the tree
+//! ends on its own line.
+//!
+//! ```text
+//! {"components":{
+//! "gold":12,
+//! "levels/":{
+//! "chest":true
+//! }
+//! },"org_name":"ABC",...}
+//! ```
+
+use std::io::{self, Write};
+
+use serde_json::{
+    ser::{CharEscape, CompactFormatter, Formatter},
+    Map, Value,
+};
+
+use crate::Salvage;
+
+const TREE_START: &[u8] = br#"{"components":{"#;
+
+#[derive(Default)]
+pub(crate) struct Lines {
+    // per open object or array, whether it's a level of the components tree
+    levels: Vec<bool>,
+    // the key being written, while one is
+    key: Option<String>,
+    next_is_tree: bool,
+}
+
+impl Formatter for Lines {
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.levels.push(std::mem::take(&mut self.next_is_tree));
+        writer.write_all(b"{")
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        match self.levels.pop() {
+            Some(true) => writer.write_all(b"\n}"),
+            _ => writer.write_all(b"}"),
+        }
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.levels.push(false);
+        writer.write_all(b"[")
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.levels.pop();
+        writer.write_all(b"]")
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if !first {
+            writer.write_all(b",")?;
+        }
+        if self.levels.last() == Some(&true) {
+            writer.write_all(b"\n")?;
+        }
+
+        self.key = Some(String::new());
+        Ok(())
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        let key = self.key.take().unwrap_or_default();
+
+        // the document's components, and the namespaces inside them
+        self.next_is_tree = match self.levels.as_slice() {
+            [_] => key == "components",
+            [.., tree] => *tree && key.ends_with('/'),
+            [] => false,
+        };
+
+        writer.write_all(b":")
+    }
+
+    fn end_object_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> io::Result<()> {
+        self.next_is_tree = false;
+        Ok(())
+    }
+
+    fn write_string_fragment<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()> {
+        if let Some(key) = &mut self.key {
+            key.push_str(fragment);
+        }
+
+        writer.write_all(fragment.as_bytes())
+    }
+
+    fn write_char_escape<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        char_escape: CharEscape,
+    ) -> io::Result<()> {
+        // escapes never produce a `/`, which is all keys are checked for
+        if let Some(key) = &mut self.key {
+            key.push('\\');
+        }
+
+        CompactFormatter.write_char_escape(writer, char_escape)
+    }
+}
+
+/// Reads the components of a document written with [`Lines`] one line at a time.
+pub(crate) fn salvage(bytes: &[u8]) -> Option<Salvage<Value>> {
+    let mut lines = bytes.split(|&byte| byte == b'\n');
+    if lines.next()? != TREE_START {
+        return None;
+    }
+
+    // the path of every open namespace, `None` below one whose name is unreadable
+    let mut namespaces: Vec<Option<String>> = Vec::new();
+    let mut components = Vec::new();
+
+    for line in lines {
+        let entry = line.strip_suffix(b",").unwrap_or(line);
+        let namespace = namespaces.last().cloned().unwrap_or(Some(String::new()));
+
+        if let Some(after) = entry.strip_prefix(b"}") {
+            if namespaces.pop().is_some() {
+                continue;
+            }
+
+            // the end of the tree, followed by the rest of the document
+            let rest = [b"{", after.strip_prefix(b",")?].concat();
+
+            return Some(Salvage {
+                components,
+                rest: serde_json::from_slice(&rest).ok()?,
+            });
+        }
+
+        if let Some(name) = entry.strip_suffix(b":{") {
+            let name = serde_json::from_slice::<String>(name)
+                .ok()
+                .filter(|name| name.ends_with('/'));
+
+            namespaces.push(namespace.zip(name).map(|(path, name)| path + &name));
+            continue;
+        }
+
+        let (key, value) = component(namespace, entry);
+
+        // the rest of a line split by a damaged byte, whose component is already reported
+        let continued = matches!(components.last(), Some((_, None)));
+        if value.is_none() && continued && !entry.starts_with(b"\"") {
+            continue;
+        }
+
+        components.push((key, value));
+    }
+
+    None
+}
+
+// one `"key":value` line, with the value left out if it can't be decoded
+fn component(namespace: Option<String>, entry: &[u8]) -> (String, Option<Value>) {
+    let decoded = serde_json::from_slice::<Map<String, Value>>(&[b"{", entry, b"}"].concat());
+
+    if let Ok(map) = decoded {
+        if let (Some(namespace), Some((key, value))) = (&namespace, map.into_iter().next()) {
+            return (format!("{}{}", namespace, key), Some(value));
+        }
+    }
+
+    let key = serde_json::Deserializer::from_slice(entry)
+        .into_iter::<String>()
+        .next()
+        .and_then(Result::ok);
+
+    match (namespace, key) {
+        (Some(namespace), Some(key)) => (namespace + &key, None),
+        (None, Some(key)) => (key, None),
+        (_, None) => (String::from_utf8_lossy(entry).into_owned(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn write(value: &Value) -> String {
+        let mut bytes = Vec::new();
+        let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, Lines::default());
+        serde::Serialize::serialize(value, &mut serializer).unwrap();
+
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_lines_layout() {
+        let document = json!({
+            "components": {
+                "gold": 12,
+                "player": { "name": "Hero", "pets/": ["cat"] },
+                "levels/": { "chest": true, "cave/": {} }
+            },
+            "org_name": "ABC",
+        });
+
+        assert_eq!(
+            write(&document),
+            "{\"components\":{\n\"gold\":12,\n\"levels/\":{\n\"cave/\":{\n},\n\"chest\":true\n},\n\"player\":{\"name\":\"Hero\",\"pets/\":[\"cat\"]}\n},\"org_name\":\"ABC\"}"
+        );
+
+        // and it's still plain JSON
+        assert_eq!(
+            serde_json::from_str::<Value>(&write(&document)).unwrap(),
+            document
+        );
+    }
+
+    #[test]
+    fn test_salvaging_lines() {
+        let document = json!({
+            "components": {
+                "gold": 12,
+                "name": "Hero",
+                "levels/": { "chest": true, "door": "open" }
+            },
+            "org_name": "ABC",
+        });
+        let damaged = write(&document)
+            .replace("12,", "1{,")
+            .replace("\"door\"", "\"do\u{1}r\"");
+
+        let salvage = salvage(damaged.as_bytes()).unwrap();
+        assert_eq!(
+            salvage.components,
+            [
+                ("gold".to_string(), None),
+                ("levels/chest".to_string(), Some(json!(true))),
+                ("\"do\u{1}r\":\"open\"".to_string(), None),
+                ("name".to_string(), Some(json!("Hero"))),
+            ]
+        );
+        assert_eq!(salvage.rest, json!({ "org_name": "ABC" }));
+
+        // without the rest of the document there's nothing to rebuild the save from
+        let truncated = &damaged[..damaged.rfind('}').unwrap() - 5];
+        assert!(super::salvage(truncated.as_bytes()).is_none());
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use crate::SaveFileError;
+
+/// Where a [`SaveFile`](crate::SaveFile) puts its files.
+///
+/// The platform directories and `ExecutableDir` get the organization name appended,
+/// explicit paths and environment variable overrides are used as is.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SaveLocation {
+    /// The user's data directory, e.g. `~/.local/share` or `%APPDATA%`.
+    #[default]
+    DataDir,
+    /// The user's config directory, e.g. `~/.config` or `%APPDATA%`.
+    ConfigDir,
+    /// The user's cache directory, e.g. `~/.cache` or `%LOCALAPPDATA%`.
+    CacheDir,
+    /// An explicit directory.
+    Path(PathBuf),
+    /// The directory the running executable is in, for portable builds.
+    ExecutableDir,
+    /// The directory named by an environment variable, or `fallback` when it isn't set.
+    Env {
+        var: String,
+        fallback: Box<SaveLocation>,
+    },
+}
+
+impl SaveLocation {
+    /// Lets `var` override the location, e.g. `SaveLocation::env("MYGAME_SAVE_DIR", SaveLocation::DataDir)`.
+    pub fn env(var: &str, fallback: SaveLocation) -> Self {
+        SaveLocation::Env {
+            var: var.to_string(),
+            fallback: Box::new(fallback),
+        }
+    }
+
+    /// The save directory for `org_name` at this location.
+    pub fn resolve(&self, org_name: &str) -> Result<PathBuf, SaveFileError> {
+        let root = match self {
+            SaveLocation::DataDir => dirs::data_dir(),
+            SaveLocation::ConfigDir => dirs::config_dir(),
+            SaveLocation::CacheDir => dirs::cache_dir(),
+            SaveLocation::Path(path) => return Ok(path.clone()),
+            SaveLocation::ExecutableDir => std::env::current_exe()?
+                .parent()
+                .map(|dir| dir.to_path_buf()),
+            SaveLocation::Env { var, fallback } => {
+                return match std::env::var_os(var) {
+                    Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+                    _ => fallback.resolve(org_name),
+                };
+            }
+        };
+
+        let root = root.ok_or(SaveFileError::NoSaveDir)?;
+
+        Ok(root.join(org_name))
+    }
+}
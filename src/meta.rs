@@ -0,0 +1,297 @@
+use std::fmt;
+
+/// Bookkeeping kept next to a component's value. Only non-default entries are stored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ComponentMeta {
+    pub(crate) version: u32,
+    /// The `type_tag` of the type the component was written as, missing for older saves.
+    pub(crate) type_name: Option<String>,
+}
+
+impl ComponentMeta {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == ComponentMeta::default()
+    }
+}
+
+/// `T`'s type name without module paths, references or the wrappers serde writes
+/// transparently, so moving a type to another module doesn't invalidate saves and
+/// `&str`, `Box<str>` and `String` all match.
+pub(crate) fn type_tag<T: ?Sized>() -> String {
+    let full = std::any::type_name::<T>();
+    let mut rest = full;
+
+    match TypeName::parse(&mut rest) {
+        Some(name) if rest.is_empty() => name.to_string(),
+        // fn pointers, trait objects and the like can't be components anyway
+        _ => full.to_string(),
+    }
+}
+
+/// Whether a component written as the `stored` tag can be read as the `requested` one.
+/// Besides equal tags, serde reads a value and its `Option`, and any sequence, set or map
+/// from another of the same kind, so those aren't treated as a mismatch.
+pub(crate) fn tags_match(stored: &str, requested: &str) -> bool {
+    match (TypeName::from_tag(stored), TypeName::from_tag(requested)) {
+        (Some(stored), Some(requested)) => stored.matches(&requested),
+        _ => stored == requested,
+    }
+}
+
+// wrappers with the same serialized form as their first type argument
+const TRANSPARENT: &[&str] = &[
+    "Box", "Rc", "Arc", "Cow", "Cell", "RefCell", "Mutex", "RwLock",
+];
+
+enum TypeName {
+    Named(String, Vec<TypeName>),
+    Tuple(Vec<TypeName>),
+    Slice(Box<TypeName>),
+    Array(Box<TypeName>, String),
+}
+
+// collections serde writes the same way, by the name of the one standing in for them all
+fn collection_kind(name: &str) -> &str {
+    match name {
+        "VecDeque" | "LinkedList" | "BinaryHeap" | "HashSet" | "BTreeSet" => "Vec",
+        "BTreeMap" => "HashMap",
+        _ => name,
+    }
+}
+
+impl TypeName {
+    fn from_tag(tag: &str) -> Option<TypeName> {
+        let mut rest = tag;
+        TypeName::parse(&mut rest).filter(|_| rest.is_empty())
+    }
+
+    fn matches(&self, other: &TypeName) -> bool {
+        let both = |ours: &[TypeName], theirs: &[TypeName]| {
+            ours.len() == theirs.len() && ours.iter().zip(theirs).all(|(a, b)| a.matches(b))
+        };
+
+        match (self.without_option(), other.without_option()) {
+            (TypeName::Tuple(ours), TypeName::Tuple(theirs)) => both(ours, theirs),
+            (TypeName::Array(ours, len), TypeName::Array(theirs, other_len)) => {
+                len == other_len && ours.matches(theirs)
+            }
+            (ours, theirs) => match (ours.named(), theirs.named()) {
+                (Some((name, ours)), Some((other_name, theirs))) => {
+                    collection_kind(name) == collection_kind(other_name) && both(ours, theirs)
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn without_option(&self) -> &TypeName {
+        match self {
+            TypeName::Named(name, args) if name == "Option" && args.len() == 1 => &args[0],
+            _ => self,
+        }
+    }
+
+    // slices are written as vectors
+    fn named(&self) -> Option<(&str, &[TypeName])> {
+        match self {
+            TypeName::Named(name, args) => Some((name, args)),
+            TypeName::Slice(inner) => Some(("Vec", std::slice::from_ref(&**inner))),
+            _ => None,
+        }
+    }
+
+    fn parse(rest: &mut &str) -> Option<TypeName> {
+        let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+        if let Some(after) = rest.strip_prefix("&mut ") {
+            *rest = after;
+            return TypeName::parse(rest);
+        }
+        if let Some(after) = rest.strip_prefix('&') {
+            *rest = after;
+            return TypeName::parse(rest);
+        }
+        if let Some(after) = rest.strip_prefix('(') {
+            *rest = after;
+            return Some(TypeName::Tuple(TypeName::parse_list(rest, ')')?));
+        }
+        if let Some(after) = rest.strip_prefix('[') {
+            *rest = after;
+            let inner = Box::new(TypeName::parse(rest)?);
+
+            if let Some(after) = rest.strip_prefix("; ") {
+                let end = after.find(']')?;
+                let len = after[..end].to_string();
+                *rest = &after[end + 1..];
+
+                return Some(TypeName::Array(inner, len));
+            }
+
+            *rest = rest.strip_prefix(']')?;
+            return Some(TypeName::Slice(inner));
+        }
+
+        // a path, of which only the last segment is kept
+        let mut name;
+        loop {
+            let end = rest.find(|c| !is_ident(c)).unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            name = &rest[..end];
+            *rest = &rest[end..];
+
+            match rest.strip_prefix("::") {
+                Some(after) => *rest = after,
+                None => break,
+            }
+        }
+
+        let mut args = Vec::new();
+        if let Some(after) = rest.strip_prefix('<') {
+            *rest = after;
+            args = TypeName::parse_list(rest, '>')?;
+        }
+
+        if TRANSPARENT.contains(&name) && !args.is_empty() {
+            return Some(args.swap_remove(0));
+        }
+
+        // the hasher doesn't change how a map or set is written
+        match (name, args.len()) {
+            ("HashMap", 3) | ("HashSet", 2) => {
+                args.pop();
+            }
+            _ => {}
+        }
+
+        let name = if name == "str" { "String" } else { name };
+
+        Some(TypeName::Named(name.to_string(), args))
+    }
+
+    // comma separated types up to and including `close`
+    fn parse_list(rest: &mut &str, close: char) -> Option<Vec<TypeName>> {
+        let mut items = Vec::new();
+
+        loop {
+            if let Some(after) = rest.strip_prefix(close) {
+                *rest = after;
+                return Some(items);
+            }
+
+            // lifetimes don't show up in the serialized form
+            if let Some(after) = rest.strip_prefix('\'') {
+                let end = after.find([',', '>']).unwrap_or(after.len());
+                *rest = &after[end..];
+            } else {
+                items.push(TypeName::parse(rest)?);
+            }
+
+            if let Some(after) = rest.strip_prefix(", ") {
+                *rest = after;
+            } else if !rest.starts_with(close) {
+                return None;
+            }
+        }
+    }
+}
+
+impl fmt::Display for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter, items: &[TypeName]| {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{}", item)?;
+            }
+            Ok(())
+        };
+
+        match self {
+            TypeName::Named(name, args) if args.is_empty() => f.write_str(name),
+            TypeName::Named(name, args) => {
+                write!(f, "{}<", name)?;
+                list(f, args)?;
+                f.write_str(">")
+            }
+            TypeName::Tuple(items) => {
+                f.write_str("(")?;
+                list(f, items)?;
+                // a one element tuple keeps its trailing comma
+                if items.len() == 1 {
+                    f.write_str(",")?;
+                }
+                f.write_str(")")
+            }
+            // serde writes slices the same way as vectors
+            TypeName::Slice(inner) => write!(f, "Vec<{}>", inner),
+            TypeName::Array(inner, len) => write!(f, "[{}; {}]", inner, len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_tag_strips_paths_and_references() {
+        struct Player;
+
+        assert_eq!(type_tag::<Player>(), "Player");
+        assert_eq!(type_tag::<&str>(), "String");
+        assert_eq!(type_tag::<&mut Vec<u8>>(), "Vec<u8>");
+        assert_eq!(
+            type_tag::<std::collections::HashMap<String, Option<&str>>>(),
+            "HashMap<String, Option<String>>"
+        );
+        assert_eq!(type_tag::<(i32, [bool; 3])>(), "(i32, [bool; 3])");
+        assert_eq!(type_tag::<(u8,)>(), "(u8,)");
+        assert_eq!(type_tag::<()>(), "()");
+    }
+
+    #[test]
+    fn test_type_tag_strips_transparent_wrappers() {
+        use std::{
+            borrow::Cow,
+            cell::RefCell,
+            collections::{hash_map::RandomState, HashMap, HashSet},
+            rc::Rc,
+            sync::{Arc, Mutex},
+        };
+
+        assert_eq!(type_tag::<Box<str>>(), "String");
+        assert_eq!(type_tag::<Cow<str>>(), "String");
+        assert_eq!(type_tag::<Arc<Mutex<Vec<u8>>>>(), "Vec<u8>");
+        assert_eq!(type_tag::<Rc<RefCell<Option<Box<i32>>>>>(), "Option<i32>");
+        assert_eq!(type_tag::<&[u8]>(), "Vec<u8>");
+        assert_eq!(type_tag::<Box<[Arc<str>]>>(), "Vec<String>");
+        assert_eq!(
+            type_tag::<HashMap<String, u8, rustc_hash::FxBuildHasher>>(),
+            type_tag::<HashMap<String, u8>>()
+        );
+        assert_eq!(type_tag::<HashSet<u8, RandomState>>(), "HashSet<u8>");
+    }
+
+    #[test]
+    fn test_tags_match_what_serde_reads() {
+        assert!(tags_match("Vec<u8>", "Vec<u8>"));
+        assert!(tags_match("Vec<u8>", "Option<Vec<u8>>"));
+        assert!(tags_match("Option<u8>", "u8"));
+        assert!(tags_match("Vec<u8>", "VecDeque<Option<u8>>"));
+        assert!(tags_match(
+            "HashMap<String, Vec<u8>>",
+            "BTreeMap<String, BTreeSet<u8>>"
+        ));
+        assert!(tags_match("(u8,)", "(u8,)"));
+
+        assert!(!tags_match("Vec<u8>", "Vec<u16>"));
+        assert!(!tags_match("Vec<u8>", "HashMap<u8, u8>"));
+        assert!(!tags_match("Vec<u8>", "[u8; 3]"));
+        assert!(!tags_match("[u8; 2]", "[u8; 3]"));
+        assert!(!tags_match("Option<Option<u8>>", "u8"));
+        assert!(!tags_match("bool", "i32"));
+    }
+}
@@ -0,0 +1,169 @@
+use std::{collections::BTreeMap, fmt, marker::PhantomData, sync::Arc};
+
+use rustc_hash::FxHashMap;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{meta::type_tag, Format, Json, SaveFile, SaveFileError};
+
+/// An upgrade step. It receives the version it upgrades from and the save file to modify in place.
+pub type Migration<F = Json> = fn(u32, &mut SaveFile<F>);
+
+type Upcaster<F> =
+    Arc<dyn Fn(&<F as Format>::Value) -> Result<<F as Format>::Value, SaveFileError> + Send + Sync>;
+
+/// Upgrade steps that bring an older [`SaveFile`] up to the current schema version.
+///
+/// A step registered for version `n` upgrades a save from `n` to `n + 1`. Versions
+/// without a registered step are considered compatible and are skipped.
+///
+/// Individual components can also be versioned on their own with
+/// [`register_upcaster`](MigrationRegistry::register_upcaster).
+pub struct MigrationRegistry<F: Format = Json> {
+    steps: BTreeMap<u32, Migration<F>>,
+    upcasters: FxHashMap<String, BTreeMap<u32, Upcaster<F>>>,
+    format: PhantomData<fn() -> F>,
+}
+
+impl<F: Format> MigrationRegistry<F> {
+    pub fn new() -> Self {
+        MigrationRegistry {
+            steps: BTreeMap::new(),
+            upcasters: FxHashMap::default(),
+            format: PhantomData,
+        }
+    }
+
+    pub fn register(&mut self, from_version: u32, step: Migration<F>) {
+        self.steps.insert(from_version, step);
+    }
+
+    /// Registers a conversion of the component stored under `key` from `from_version` to
+    /// `from_version + 1`. The current version of a component is one past its newest upcaster.
+    pub fn register_upcaster<Old, New, U>(&mut self, key: &str, from_version: u32, upcast: U)
+    where
+        Old: DeserializeOwned,
+        New: Serialize,
+        U: Fn(Old) -> New + Send + Sync + 'static,
+    {
+        let owned_key = key.to_string();
+
+        let upcaster: Upcaster<F> = Arc::new(move |value| {
+            let old: Old = F::from_value(value).map_err(|source| SaveFileError::TypeMismatch {
+                key: owned_key.clone(),
+                stored: None,
+                requested: type_tag::<Old>(),
+                source: Some(source),
+            })?;
+
+            Ok(F::to_value(&upcast(old))?)
+        });
+
+        self.upcasters
+            .entry(key.to_string())
+            .or_default()
+            .insert(from_version, upcaster);
+    }
+
+    /// The version new data for `key` is written with.
+    pub fn component_version(&self, key: &str) -> u32 {
+        self.current_version(key).unwrap_or(0)
+    }
+
+    /// The current version of `key`, if any upcasters are registered for it.
+    pub(crate) fn current_version(&self, key: &str) -> Option<u32> {
+        self.upcasters
+            .get(key)
+            .and_then(|upcasters| upcasters.keys().next_back())
+            .map(|newest| newest + 1)
+    }
+
+    /// Chains the upcasters for `key` to bring `value` from `found` to `supported`.
+    pub(crate) fn upcast(
+        &self,
+        key: &str,
+        found: u32,
+        supported: u32,
+        value: &F::Value,
+    ) -> Result<F::Value, SaveFileError> {
+        if found > supported {
+            return Err(SaveFileError::UnsupportedComponentVersion {
+                key: key.to_string(),
+                found,
+                supported,
+            });
+        }
+
+        let mut value = value.clone();
+
+        for from_version in found..supported {
+            let upcaster = self
+                .upcasters
+                .get(key)
+                .and_then(|upcasters| upcasters.get(&from_version))
+                .ok_or_else(|| SaveFileError::MissingUpcaster {
+                    key: key.to_string(),
+                    from_version,
+                })?;
+
+            value = upcaster(&value)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Runs every pending step in order until `save_file` reaches `target_version`.
+    pub(crate) fn run(
+        &self,
+        save_file: &mut SaveFile<F>,
+        target_version: u32,
+    ) -> Result<(), SaveFileError> {
+        let found = save_file.version();
+
+        if found > target_version {
+            return Err(SaveFileError::UnsupportedVersion {
+                found,
+                supported: target_version,
+            });
+        }
+
+        for (&from_version, step) in self.steps.range(found..target_version) {
+            step(from_version, save_file);
+            save_file.set_version(from_version + 1);
+        }
+
+        save_file.set_version(target_version);
+
+        Ok(())
+    }
+}
+
+impl<F: Format> Default for MigrationRegistry<F> {
+    fn default() -> Self {
+        MigrationRegistry::new()
+    }
+}
+
+impl<F: Format> Clone for MigrationRegistry<F> {
+    fn clone(&self) -> Self {
+        MigrationRegistry {
+            steps: self.steps.clone(),
+            upcasters: self.upcasters.clone(),
+            format: PhantomData,
+        }
+    }
+}
+
+impl<F: Format> fmt::Debug for MigrationRegistry<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let upcasters: BTreeMap<&str, Vec<&u32>> = self
+            .upcasters
+            .iter()
+            .map(|(key, upcasters)| (key.as_str(), upcasters.keys().collect()))
+            .collect();
+
+        f.debug_struct("MigrationRegistry")
+            .field("steps", &self.steps)
+            .field("upcasters", &upcasters)
+            .finish()
+    }
+}
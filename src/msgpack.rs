@@ -0,0 +1,48 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, FormatError};
+
+/// [MessagePack](https://msgpack.org), a compact binary format that still says what
+/// each value is. Structs are written as arrays, without their field names, and
+/// components are embedded as MessagePack values. Needs the `msgpack` feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPack;
+
+impl Format for MsgPack {
+    const NAME: &'static str = "msgpack";
+
+    type Value = rmpv::Value;
+
+    fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(rmpv::ext::to_value(value)?)
+    }
+
+    fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(rmpv::ext::from_value(value.clone())?)
+    }
+
+    fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(rmp_serde::to_vec(value)?)
+    }
+
+    fn from_slice<T>(mut bytes: &[u8]) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        let value = rmp_serde::from_read(&mut bytes)?;
+
+        match bytes.is_empty() {
+            true => Ok(value),
+            false => Err("trailing bytes".into()),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeSet;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, Json, SaveFile, SaveFileError};
+
+/// A view of the components under one path of a [`SaveFile`], from
+/// [`SaveFile::namespace`]. Keys are relative to the namespace, e.g. `"chest"` in
+/// `levels/forest` is stored as `"levels/forest/chest"`.
+pub struct Namespace<'a, F: Format = Json> {
+    save_file: &'a mut SaveFile<F>,
+    // the normalized path followed by a `/`, empty for the root
+    prefix: String,
+}
+
+impl<'a, F: Format> Namespace<'a, F> {
+    pub(crate) fn new(save_file: &'a mut SaveFile<F>, path: &str) -> Self {
+        let mut prefix = String::new();
+
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            prefix.push_str(segment);
+            prefix.push('/');
+        }
+
+        Namespace { save_file, prefix }
+    }
+
+    /// The namespace's path, e.g. `"levels/forest"`.
+    pub fn path(&self) -> &str {
+        self.prefix.strip_suffix('/').unwrap_or_default()
+    }
+
+    /// A namespace nested inside this one.
+    pub fn namespace(&mut self, path: &str) -> Namespace<'_, F> {
+        let full = format!("{}{}", self.prefix, path);
+
+        Namespace::new(self.save_file, &full)
+    }
+
+    pub fn add_component<T>(&mut self, key: &str, value: T) -> Result<(), SaveFileError>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.save_file.add_component(self.full_key(key), value)
+    }
+
+    pub fn get_component<T>(&self, key: &str) -> Result<T, SaveFileError>
+    where
+        T: DeserializeOwned,
+    {
+        self.save_file.get_component(&self.full_key(key))
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.save_file.contains_key(&self.full_key(key))
+    }
+
+    /// Removes a component, returning its stored value if it existed.
+    pub fn remove_component(&mut self, key: &str) -> Option<F::Value> {
+        self.save_file.remove_component(&self.full_key(key))
+    }
+
+    /// The keys of the components directly in this namespace, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.children().filter(|rest| !rest.contains('/'))
+    }
+
+    /// The names of the namespaces directly inside this one, sorted.
+    pub fn namespaces(&self) -> Vec<&str> {
+        let names: BTreeSet<&str> = self
+            .children()
+            .filter_map(|rest| rest.split_once('/').map(|(name, _)| name))
+            .collect();
+
+        names.into_iter().collect()
+    }
+
+    /// Removes every component in this namespace and the namespaces inside it.
+    pub fn remove(self) {
+        let prefix = self.prefix;
+
+        self.save_file.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    // every key below this namespace, relative to it
+    fn children(&self) -> impl Iterator<Item = &str> {
+        self.save_file
+            .keys()
+            .filter_map(|key| key.strip_prefix(self.prefix.as_str()))
+    }
+}
+
+/// Writes components as a tree where every namespace is a nested map under its name
+/// followed by a `/`, and reads both that and flat maps back into flat `a/b/c` keys.
+/// Within a namespace its components come first, then the nested namespaces, each sorted.
+pub(crate) mod tree {
+    use std::{collections::BTreeMap, fmt};
+
+    use serde::{
+        de::{DeserializeSeed, MapAccess, Visitor},
+        ser::SerializeMap,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    /// Serializes a flat list of components as a tree.
+    pub(crate) struct Tree<'a, V>(pub(crate) Vec<(&'a str, V)>);
+
+    impl<V: Serialize> Serialize for Tree<'_, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Branch::new(&self.0).serialize(serializer)
+        }
+    }
+
+    struct Branch<'a, V> {
+        // keyed by the last path segment
+        components: BTreeMap<&'a str, &'a V>,
+        namespaces: BTreeMap<String, Branch<'a, V>>,
+    }
+
+    impl<V> Default for Branch<'_, V> {
+        fn default() -> Self {
+            Branch {
+                components: BTreeMap::new(),
+                namespaces: BTreeMap::new(),
+            }
+        }
+    }
+
+    impl<'a, V> Branch<'a, V> {
+        fn new(components: &'a [(&'a str, V)]) -> Self {
+            let mut root = Branch::default();
+
+            for (key, value) in components {
+                let mut segments: Vec<&str> = key.split('/').collect();
+                let name = segments.pop().unwrap_or_default();
+
+                let branch = segments.into_iter().fold(&mut root, |branch, segment| {
+                    branch
+                        .namespaces
+                        .entry(format!("{}/", segment))
+                        .or_default()
+                });
+                branch.components.insert(name, value);
+            }
+
+            root
+        }
+    }
+
+    impl<V: Serialize> Serialize for Branch<'_, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map =
+                serializer.serialize_map(Some(self.components.len() + self.namespaces.len()))?;
+
+            for (name, value) in &self.components {
+                map.serialize_entry(name, value)?;
+            }
+            for (name, branch) in &self.namespaces {
+                map.serialize_entry(name, branch)?;
+            }
+
+            map.end()
+        }
+    }
+
+    /// Reads a tree back into flat keys, in the order they appear in the file.
+    pub(crate) fn deserialize<'de, V, D>(deserializer: D) -> Result<Vec<(String, V)>, D::Error>
+    where
+        V: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let mut components = Vec::new();
+
+        deserializer.deserialize_map(Flatten {
+            prefix: String::new(),
+            components: &mut components,
+        })?;
+
+        Ok(components)
+    }
+
+    // adds the entries of one level of the tree to `components`, keyed by their full path
+    struct Flatten<'m, V> {
+        prefix: String,
+        components: &'m mut Vec<(String, V)>,
+    }
+
+    impl<'de, V: Deserialize<'de>> DeserializeSeed<'de> for Flatten<'_, V> {
+        type Value = ();
+
+        fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+            deserializer.deserialize_map(self)
+        }
+    }
+
+    impl<'de, V: Deserialize<'de>> Visitor<'de> for Flatten<'_, V> {
+        type Value = ();
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of components")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+            while let Some(key) = map.next_key::<String>()? {
+                let full_key = format!("{}{}", self.prefix, key);
+
+                if key.ends_with('/') {
+                    map.next_value_seed(Flatten {
+                        prefix: full_key,
+                        components: &mut *self.components,
+                    })?;
+                } else {
+                    self.components.push((full_key, map.next_value()?));
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A table of bytes with one record per component, written as base64 by human readable
+/// formats and as plain bytes by binary ones. Only read now, from container version 2
+/// saves; `runs` is kept for the tests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Packed(pub(crate) Vec<u8>);
+
+impl Packed {
+    /// The table read back as checksums, `None` if it doesn't hold exactly `count` of them.
+    pub(crate) fn to_checksums(&self, count: usize) -> Option<Vec<u32>> {
+        if self.0.len() != count * 4 {
+            return None;
+        }
+
+        Some(
+            self.0
+                .chunks_exact(4)
+                .map(|chunk| u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                .collect(),
+        )
+    }
+
+    #[cfg(test)]
+    /// Stores small numbers as runs of equal values, each a varint count and a varint value.
+    pub(crate) fn runs(values: impl IntoIterator<Item = u32>) -> Self {
+        let mut packed = Vec::new();
+        let mut values = values.into_iter().peekable();
+
+        while let Some(value) = values.next() {
+            let mut count = 1;
+            while values.next_if_eq(&value).is_some() {
+                count += 1;
+            }
+
+            write_varint(&mut packed, count);
+            write_varint(&mut packed, value);
+        }
+
+        Packed(packed)
+    }
+
+    /// The table read back as runs, `None` if they don't add up to exactly `count` values.
+    pub(crate) fn to_runs(&self, count: usize) -> Option<Vec<u32>> {
+        let mut values = Vec::with_capacity(count);
+        let mut rest = self.0.as_slice();
+
+        while !rest.is_empty() {
+            let run = read_varint(&mut rest)? as usize;
+            let value = read_varint(&mut rest)?;
+
+            if run > count - values.len() {
+                return None;
+            }
+            values.extend(std::iter::repeat_n(value, run));
+        }
+
+        (values.len() == count).then_some(values)
+    }
+}
+
+#[cfg(test)]
+fn write_varint(bytes: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u32> {
+    let mut value = 0u32;
+
+    for shift in (0..35).step_by(7) {
+        let (&byte, rest) = bytes.split_first()?;
+        *bytes = rest;
+
+        value |= ((byte & 0x7F) as u32).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+impl Serialize for Packed {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode_base64(&self.0))
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Packed {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(PackedVisitor)
+        } else {
+            deserializer.deserialize_byte_buf(PackedVisitor)
+        }
+    }
+}
+
+struct PackedVisitor;
+
+impl Visitor<'_> for PackedVisitor {
+    type Value = Packed;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("base64 or bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Packed, E> {
+        decode_base64(value)
+            .map(Packed)
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Packed, E> {
+        Ok(Packed(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<Packed, E> {
+        Ok(Packed(value))
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    if !encoded.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(encoded.len() / 4 * 3);
+
+    for chunk in encoded.chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+
+        let mut group = 0u32;
+        for (i, &c) in chunk[..4 - padding].iter().enumerate() {
+            let sextet = ALPHABET.iter().position(|&a| a == c)? as u32;
+            group |= sextet << (18 - 6 * i);
+        }
+
+        bytes.extend_from_slice(&group.to_be_bytes()[1..4 - padding]);
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_round_trip() {
+        assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode_base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(encode_base64(b"foob"), "Zm9vYg==");
+        assert_eq!(encode_base64(b""), "");
+
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 37) as u8).collect();
+            assert_eq!(decode_base64(&encode_base64(&bytes)).unwrap(), bytes);
+        }
+
+        assert!(decode_base64("Zm9").is_none());
+        assert!(decode_base64("Zm9*").is_none());
+    }
+
+    #[test]
+    fn test_runs_round_trip() {
+        let values = [0, 0, 0, 300, 300, 1, 0, u32::MAX];
+        let packed = Packed::runs(values);
+
+        assert_eq!(packed.0[..2], [3, 0]);
+        assert_eq!(packed.to_runs(values.len()).unwrap(), values);
+        assert!(packed.to_runs(values.len() - 1).is_none());
+        assert!(packed.to_runs(values.len() + 1).is_none());
+        assert_eq!(Packed::runs([]).to_runs(0).unwrap(), Vec::<u32>::new());
+    }
+}
@@ -0,0 +1,124 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, FormatError};
+
+/// A compact binary format using the [postcard](https://postcard.jamesmunns.com) wire
+/// format: integers are varints, nothing but the values themselves is written and
+/// components are embedded as their encoded bytes. Needs the `postcard` feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Postcard;
+
+impl Format for Postcard {
+    const NAME: &'static str = "postcard";
+
+    type Value = Vec<u8>;
+
+    fn to_value<T>(value: &T) -> Result<Self::Value, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Postcard::to_vec(value)
+    }
+
+    fn from_value<T>(value: &Self::Value) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        Postcard::from_slice(value)
+    }
+
+    fn to_vec<T>(value: &T) -> Result<Vec<u8>, FormatError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(::postcard::to_stdvec(value)?)
+    }
+
+    fn from_slice<T>(bytes: &[u8]) -> Result<T, FormatError>
+    where
+        T: DeserializeOwned,
+    {
+        let (value, rest) = ::postcard::take_from_bytes(bytes)?;
+
+        match rest.is_empty() {
+            true => Ok(value),
+            false => Err("trailing bytes".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[test]
+    fn test_postcard_wire_format() {
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        enum Shape {
+            Point,
+            Circle(f32),
+            Rect { w: u16, h: u16 },
+        }
+
+        #[derive(Serialize, Deserialize, Debug, PartialEq)]
+        struct Everything {
+            flag: bool,
+            small: i8,
+            negative: i32,
+            big: u64,
+            name: String,
+            letter: char,
+            maybe: Option<u32>,
+            list: Vec<i16>,
+            pair: (u8, f64),
+            map: BTreeMap<String, u32>,
+            shapes: Vec<Shape>,
+            unit: (),
+        }
+
+        // reference encodings from the postcard specification
+        assert_eq!(Postcard::to_vec(&300u32).unwrap(), [0xAC, 0x02]);
+        assert_eq!(Postcard::to_vec(&-1i32).unwrap(), [0x01]);
+        assert_eq!(Postcard::to_vec(&1i32).unwrap(), [0x02]);
+        assert_eq!(Postcard::to_vec(&"hi").unwrap(), [2, b'h', b'i']);
+        assert_eq!(Postcard::to_vec(&Some(7u8)).unwrap(), [1, 7]);
+        assert_eq!(
+            Postcard::to_vec(&Shape::Rect { w: 1, h: 2 }).unwrap(),
+            [2, 1, 2]
+        );
+
+        let everything = Everything {
+            flag: true,
+            small: -5,
+            negative: -123456,
+            big: u64::MAX,
+            name: "Hero".to_string(),
+            letter: 'é',
+            maybe: None,
+            list: vec![i16::MIN, 0, i16::MAX],
+            pair: (9, -0.1),
+            map: [("gold".to_string(), 12), ("gems".to_string(), 3)].into(),
+            shapes: vec![Shape::Point, Shape::Circle(1.5), Shape::Rect { w: 4, h: 3 }],
+            unit: (),
+        };
+
+        let bytes = Postcard::to_vec(&everything).unwrap();
+        assert_eq!(
+            Postcard::from_slice::<Everything>(&bytes).unwrap(),
+            everything
+        );
+
+        // truncated and padded input are both errors
+        assert!(Postcard::from_slice::<Everything>(&bytes[..bytes.len() - 1]).is_err());
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(Postcard::from_slice::<Everything>(&padded).is_err());
+
+        // nothing in the bytes says what they are
+        assert!(Postcard::from_slice::<serde_json::Value>(&bytes).is_err());
+    }
+}
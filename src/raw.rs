@@ -0,0 +1,467 @@
+use std::{fmt, marker::PhantomData};
+
+use rustc_hash::FxHashMap;
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    ser::{Error, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    checksum::crc32, namespace::tree::Tree, packed::Packed, Format, FormatError, Salvage, SaveFile,
+};
+
+/// The layout a [`SaveFile`] is written with. Every field is always written, in the same
+/// order [`RawSaveFile`] reads them, so formats that don't write field names can read it
+/// back by position.
+#[derive(Serialize)]
+#[serde(bound = "")]
+pub(crate) struct Document<'a, F: Format> {
+    components: Tree<'a, Stored<&'a F::Value>>,
+    org_name: &'a str,
+    version: u32,
+    types: Vec<&'a str>,
+}
+
+/// A component as it's written in the tree: its value followed by its index in `types`
+/// plus one (zero if it has no recorded type), the `checksum` of it all if checksums are
+/// written, and the version it was written with. Trailing fields that are left at their
+/// default aren't written.
+///
+/// Keeping these next to the value means damage to one entry can't take the bookkeeping
+/// of the others with it.
+pub(crate) struct Stored<V> {
+    value: V,
+    type_id: u32,
+    checksum: Option<u32>,
+    version: u32,
+}
+
+impl<V: Serialize> Serialize for Stored<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = match self {
+            Stored { version: 1.., .. } => 4,
+            Stored {
+                checksum: Some(_), ..
+            } => 3,
+            Stored { type_id: 1.., .. } => 2,
+            _ => 1,
+        };
+
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        seq.serialize_element(&self.value)?;
+        if len > 1 {
+            seq.serialize_element(&self.type_id)?;
+        }
+        if len > 2 {
+            seq.serialize_element(&self.checksum)?;
+        }
+        if len > 3 {
+            seq.serialize_element(&self.version)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for Stored<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(StoredVisitor(PhantomData))
+    }
+}
+
+struct StoredVisitor<V>(PhantomData<V>);
+
+impl<'de, V: Deserialize<'de>> Visitor<'de> for StoredVisitor<V> {
+    type Value = Stored<V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a component followed by its type, checksum and version")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Stored<V>, A::Error> {
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+
+        Ok(Stored {
+            value,
+            type_id: seq.next_element()?.unwrap_or_default(),
+            checksum: seq.next_element()?.flatten(),
+            version: seq.next_element()?.unwrap_or_default(),
+        })
+    }
+}
+
+/// A [`Document`] read back.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct RawSaveFile<F: Format> {
+    #[serde(deserialize_with = "crate::namespace::tree::deserialize")]
+    components: Vec<(String, Stored<F::Value>)>,
+    org_name: String,
+    version: u32,
+    /// Every type name used by the components, each stored once.
+    types: Vec<String>,
+}
+
+/// The layout written by container version 2, with the bookkeeping of every component in
+/// tables after the components tree rather than in its entry.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct TableSaveFile<F: Format> {
+    #[serde(deserialize_with = "crate::namespace::tree::deserialize")]
+    components: Vec<(String, F::Value)>,
+    org_name: String,
+    version: u32,
+    /// The version of every component, in the order they appear in `components`.
+    versions: Packed,
+    /// Every type name used by the components, each stored once.
+    types: Vec<String>,
+    /// Per component, in the order they appear in `components`, its index in `types` plus
+    /// one, or zero if it has no recorded type.
+    type_ids: Packed,
+    /// The CRC-32 of every component, in the order they appear in `components`, if they
+    /// were written.
+    checksums: Option<Packed>,
+}
+
+/// The JSON layouts written before [`TableSaveFile`], with the components either in a
+/// `map` of encoded byte arrays or as native values, and their meta in a map keyed by
+/// component.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct LegacySaveFile<F: Format> {
+    #[serde(default, deserialize_with = "crate::namespace::tree::deserialize")]
+    components: Vec<(String, F::Value)>,
+    #[serde(default)]
+    map: FxHashMap<String, Vec<u8>>,
+    org_name: String,
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    meta: FxHashMap<String, LegacyMeta>,
+}
+
+#[derive(Deserialize)]
+struct LegacyMeta {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    checksum: Option<u32>,
+    #[serde(default, rename = "type")]
+    type_name: Option<String>,
+}
+
+/// The CRC-32 of a component's value together with its version and type name, so damage
+/// to any of them is caught.
+fn checksum<F: Format>(
+    value: &F::Value,
+    version: u32,
+    type_name: Option<&str>,
+) -> Result<u32, FormatError> {
+    F::to_vec(&(value, version, type_name)).map(|bytes| crc32(&bytes))
+}
+
+impl<'a, F: Format> Document<'a, F> {
+    pub(crate) fn new<E: Error>(save_file: &'a SaveFile<F>) -> Result<Self, E> {
+        // sorted so the type table comes out the same every time
+        let mut keys: Vec<&str> = save_file.map.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+
+        let mut types = Vec::new();
+        let mut type_ids = FxHashMap::default();
+        let mut components = Vec::with_capacity(keys.len());
+
+        for key in keys {
+            let value = &save_file.map[key];
+            let version = save_file.component_version(key);
+            let type_name = save_file.type_name(key);
+
+            let type_id = type_name.map_or(0, |name| {
+                *type_ids.entry(name).or_insert_with(|| {
+                    types.push(name);
+                    types.len() as u32
+                })
+            });
+
+            let checksum = match save_file.checksums {
+                true => Some(checksum::<F>(value, version, type_name).map_err(E::custom)?),
+                false => None,
+            };
+
+            components.push((
+                key,
+                Stored {
+                    value,
+                    type_id,
+                    checksum,
+                    version,
+                },
+            ));
+        }
+
+        Ok(Document {
+            components: Tree(components),
+            org_name: &save_file.org_name,
+            version: save_file.version,
+            types,
+        })
+    }
+}
+
+/// The components that were dropped while loading a damaged save file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Components whose data no longer matches their checksum.
+    pub corrupt: Vec<String>,
+    /// Components whose data could not be decoded at all.
+    pub unparsable: Vec<String>,
+}
+
+impl RecoveryReport {
+    /// True if every component was loaded.
+    pub fn is_clean(&self) -> bool {
+        self.corrupt.is_empty() && self.unparsable.is_empty()
+    }
+}
+
+impl fmt::Display for RecoveryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "corrupt components: {:?}, unparsable components: {:?}",
+            self.corrupt, self.unparsable
+        )
+    }
+}
+
+/// Everything in a [`RawSaveFile`] besides its components.
+#[derive(Deserialize)]
+struct RawRest {
+    org_name: String,
+    version: u32,
+    types: Vec<String>,
+}
+
+impl<F: Format> RawSaveFile<F> {
+    /// Verifies checksums, dropping every component that fails, and builds the save file
+    /// from the ones that are kept.
+    pub(crate) fn recover(self) -> (SaveFile<F>, RecoveryReport) {
+        let rest = RawRest {
+            org_name: self.org_name,
+            version: self.version,
+            types: self.types,
+        };
+        let components = self.components.into_iter();
+
+        rest.rebuild(
+            components
+                .map(|(key, stored)| (key, Some(stored)))
+                .collect(),
+        )
+    }
+
+    /// Like `recover`, but for what [`Format::salvage`] could read of a document. `None`
+    /// if the fields besides the components didn't survive.
+    pub(crate) fn recover_salvaged(
+        salvage: Salvage<F::Value>,
+    ) -> Option<(SaveFile<F>, RecoveryReport)> {
+        let rest: RawRest = F::from_value(&salvage.rest).ok()?;
+
+        let components = salvage.components.into_iter().map(|(key, entry)| {
+            let stored = entry.and_then(|entry| F::from_value(&entry).ok());
+            (key, stored)
+        });
+
+        Some(rest.rebuild(components.collect()))
+    }
+}
+
+impl RawRest {
+    fn rebuild<F: Format>(
+        self,
+        components: Vec<(String, Option<Stored<F::Value>>)>,
+    ) -> (SaveFile<F>, RecoveryReport) {
+        let mut report = RecoveryReport::default();
+
+        let mut save_file = SaveFile::with_format(self.org_name);
+        save_file.version = self.version;
+
+        for (key, stored) in components {
+            let Some(Stored {
+                value,
+                type_id,
+                checksum: expected,
+                version,
+            }) = stored
+            else {
+                report.unparsable.push(key);
+                continue;
+            };
+
+            let type_name = (type_id as usize)
+                .checked_sub(1)
+                .and_then(|index| self.types.get(index))
+                .cloned();
+
+            let intact = expected.is_none_or(|expected| {
+                checksum::<F>(&value, version, type_name.as_deref())
+                    .is_ok_and(|checksum| checksum == expected)
+            });
+
+            match intact {
+                true => save_file.restore(key, value, version, type_name),
+                false => report.corrupt.push(key),
+            }
+        }
+
+        report.corrupt.sort();
+        report.unparsable.sort();
+
+        (save_file, report)
+    }
+}
+
+/// Everything in a [`TableSaveFile`] besides its components.
+#[derive(Deserialize)]
+struct TableRest {
+    org_name: String,
+    version: u32,
+    versions: Packed,
+    types: Vec<String>,
+    type_ids: Packed,
+    checksums: Option<Packed>,
+}
+
+impl<F: Format> TableSaveFile<F> {
+    /// Verifies checksums, dropping every component that fails, and builds the save file
+    /// from the ones that are kept.
+    pub(crate) fn recover(self) -> (SaveFile<F>, RecoveryReport) {
+        let rest = TableRest {
+            org_name: self.org_name,
+            version: self.version,
+            versions: self.versions,
+            types: self.types,
+            type_ids: self.type_ids,
+            checksums: self.checksums,
+        };
+        let components = self.components.into_iter();
+
+        rest.rebuild(components.map(|(key, value)| (key, Some(value))).collect())
+    }
+
+    /// Like `recover`, but for what [`Format::salvage`] could read of a document. `None`
+    /// if the fields besides the components didn't survive.
+    pub(crate) fn recover_salvaged(
+        salvage: Salvage<F::Value>,
+    ) -> Option<(SaveFile<F>, RecoveryReport)> {
+        let rest: TableRest = F::from_value(&salvage.rest).ok()?;
+
+        Some(rest.rebuild(salvage.components))
+    }
+}
+
+impl TableRest {
+    fn rebuild<F: Format>(
+        self,
+        components: Vec<(String, Option<F::Value>)>,
+    ) -> (SaveFile<F>, RecoveryReport) {
+        let mut report = RecoveryReport::default();
+        let count = components.len();
+
+        // a table that doesn't line up with the components can't vouch for any of them
+        let mut checksums = self.checksums.map(|checksums| {
+            checksums
+                .to_checksums(count)
+                .unwrap_or_default()
+                .into_iter()
+        });
+
+        // nor can one that doesn't say which version each of them was written with
+        let versions = self.versions.to_runs(count).unwrap_or_default();
+        let mut versions = versions.into_iter();
+
+        // while a damaged type table only loses the type checks
+        let type_ids = self.type_ids.to_runs(count).unwrap_or_default();
+        let mut type_ids = type_ids.into_iter();
+
+        let mut save_file = SaveFile::with_format(self.org_name);
+        save_file.version = self.version;
+
+        for (key, value) in components {
+            // every entry has its place in the tables, even one that can't be decoded
+            let type_id = type_ids.next();
+            let checksum = checksums.as_mut().map(|checksums| checksums.next());
+            let version = versions.next();
+
+            let Some(value) = value else {
+                report.unparsable.push(key);
+                continue;
+            };
+
+            let type_name = type_id
+                .and_then(|id| self.types.get((id as usize).checked_sub(1)?))
+                .cloned();
+
+            let intact = checksum.is_none_or(|checksum| {
+                checksum.is_some_and(|expected| {
+                    F::to_vec(&value).is_ok_and(|bytes| crc32(&bytes) == expected)
+                })
+            });
+
+            match version.filter(|_| intact) {
+                Some(version) => save_file.restore(key, value, version, type_name),
+                None => report.corrupt.push(key),
+            }
+        }
+
+        report.corrupt.sort();
+        report.unparsable.sort();
+
+        (save_file, report)
+    }
+}
+
+impl<F: Format> LegacySaveFile<F> {
+    /// Decodes any byte array components and verifies the checksums of the others,
+    /// dropping every component that fails either step.
+    pub(crate) fn recover(mut self) -> (SaveFile<F>, RecoveryReport) {
+        let mut report = RecoveryReport::default();
+
+        let mut save_file = SaveFile::with_format(self.org_name);
+        save_file.version = self.version;
+
+        for (key, value) in self.components {
+            let meta = self.meta.remove(&key);
+
+            let intact = meta
+                .as_ref()
+                .and_then(|meta| meta.checksum)
+                .is_none_or(|expected| {
+                    F::to_vec(&value).is_ok_and(|bytes| crc32(&bytes) == expected)
+                });
+            if !intact {
+                report.corrupt.push(key);
+                continue;
+            }
+
+            let (version, type_name) =
+                meta.map_or((0, None), |meta| (meta.version, meta.type_name));
+            save_file.restore(key, value, version, type_name);
+        }
+
+        for (key, bytes) in self.map {
+            let version = self.meta.get(&key).map_or(0, |meta| meta.version);
+
+            match F::from_slice(&bytes) {
+                Ok(value) => save_file.restore(key, value, version, None),
+                Err(_) => report.unparsable.push(key),
+            }
+        }
+
+        report.corrupt.sort();
+        report.unparsable.sort();
+
+        (save_file, report)
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{atomic, backup, Format, SaveFile, SaveFileError, SaveHeader};
+
+/// A save slot found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+    /// The file's header, `None` for legacy saves and unreadable files.
+    pub header: Option<SaveHeader>,
+}
+
+/// Manages named save slots ("Slot 1", "Autosave", ...) stored as `<name>.<extension>`
+/// files in a save directory.
+#[derive(Debug, Clone)]
+pub struct SaveSlots {
+    dir: PathBuf,
+    extension: String,
+}
+
+impl SaveSlots {
+    pub fn new(dir: PathBuf) -> Self {
+        SaveSlots {
+            dir,
+            extension: "save".to_string(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Changes the file extension slots are stored with, `save` by default.
+    pub fn set_extension(&mut self, extension: &str) {
+        self.extension = extension.to_string();
+    }
+
+    /// The file a slot is stored in.
+    pub fn path(&self, name: &str) -> Result<PathBuf, SaveFileError> {
+        let invalid = name.is_empty()
+            || name.starts_with('.')
+            || name.contains(['/', '\\'])
+            || Path::new(name).components().count() != 1;
+
+        if invalid {
+            return Err(SaveFileError::InvalidSlotName(name.to_string()));
+        }
+
+        Ok(self.dir.join(format!("{}.{}", name, self.extension)))
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.path(name).is_ok_and(|path| path.is_file())
+    }
+
+    /// Every slot in the directory, sorted by name.
+    pub fn list(&self) -> Result<Vec<SlotInfo>, SaveFileError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut slots = vec![];
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some(self.extension.as_str()) {
+                continue;
+            }
+
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                Some(name) if !name.starts_with('.') => name.to_string(),
+                _ => continue,
+            };
+
+            let metadata = fs::metadata(&path)?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            slots.push(SlotInfo {
+                name,
+                header: SaveHeader::read(&path).ok().flatten(),
+                path,
+                size: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+
+        slots.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(slots)
+    }
+
+    /// The slot that was written most recently, going by the `modified` time in its header.
+    /// Slots saved within the same second, and legacy saves without a header, are ordered by
+    /// their file's modification time.
+    pub fn most_recent(&self) -> Result<Option<SlotInfo>, SaveFileError> {
+        Ok(self.list()?.into_iter().max_by_key(|slot| {
+            let file_modified = slot.modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+            let saved = slot
+                .header
+                .as_ref()
+                .map_or(file_modified.as_secs(), |header| header.modified);
+
+            (saved, file_modified)
+        }))
+    }
+
+    /// Writes `save_file` to a new slot, failing if the slot is already taken.
+    pub fn create<F: Format>(
+        &self,
+        name: &str,
+        save_file: &SaveFile<F>,
+    ) -> Result<(), SaveFileError> {
+        if self.exists(name) {
+            return Err(SaveFileError::SlotExists(name.to_string()));
+        }
+
+        self.save(name, save_file)
+    }
+
+    /// Writes `save_file` to a slot, replacing what was there.
+    pub fn save<F: Format>(
+        &self,
+        name: &str,
+        save_file: &SaveFile<F>,
+    ) -> Result<(), SaveFileError> {
+        save_file.save_to_file(self.path(name)?)
+    }
+
+    /// Loads a slot using `loader`'s format, migrations and settings.
+    pub fn load<F: Format>(
+        &self,
+        name: &str,
+        loader: &SaveFile<F>,
+    ) -> Result<SaveFile<F>, SaveFileError> {
+        loader.load_from_file(self.existing_path(name)?)
+    }
+
+    pub fn copy(&self, from: &str, to: &str) -> Result<(), SaveFileError> {
+        let source = self.existing_path(from)?;
+        let target = self.vacant_path(to)?;
+
+        atomic::write_atomic(&target, &fs::read(source)?)?;
+
+        Ok(())
+    }
+
+    /// Renames a slot along with its backups.
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), SaveFileError> {
+        let source = self.existing_path(from)?;
+        let target = self.vacant_path(to)?;
+
+        for (i, old_backup) in backup::existing_backups(&source).iter().enumerate() {
+            fs::rename(old_backup, backup::backup_path(&target, i + 1))?;
+        }
+
+        fs::rename(source, target)?;
+
+        Ok(())
+    }
+
+    /// Deletes a slot along with its backups.
+    pub fn delete(&self, name: &str) -> Result<(), SaveFileError> {
+        let path = self.existing_path(name)?;
+
+        for old_backup in backup::existing_backups(&path) {
+            fs::remove_file(old_backup)?;
+        }
+
+        fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    fn existing_path(&self, name: &str) -> Result<PathBuf, SaveFileError> {
+        let path = self.path(name)?;
+
+        if !path.is_file() {
+            return Err(SaveFileError::SlotNotFound(name.to_string()));
+        }
+
+        Ok(path)
+    }
+
+    fn vacant_path(&self, name: &str) -> Result<PathBuf, SaveFileError> {
+        let path = self.path(name)?;
+
+        if path.exists() {
+            return Err(SaveFileError::SlotExists(name.to_string()));
+        }
+
+        Ok(path)
+    }
+}
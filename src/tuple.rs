@@ -0,0 +1,43 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Format, SaveFile};
+
+/// A tuple of component types that can be read from a [`SaveFile`] in one call.
+///
+/// Each element is read independently, so a missing or undecodable component
+/// only turns its own slot into `None`.
+pub trait ComponentTuple {
+    type Keys<'k>;
+    type Output;
+
+    fn get_from<F: Format>(save_file: &SaveFile<F>, keys: Self::Keys<'_>) -> Self::Output;
+}
+
+macro_rules! impl_component_tuple {
+    ($len:expr => $($name:ident $idx:tt),+) => {
+        impl<$($name),+> ComponentTuple for ($($name,)+)
+        where
+            $($name: Serialize + DeserializeOwned,)+
+        {
+            type Keys<'k> = [&'k str; $len];
+            type Output = ($(Option<$name>,)+);
+
+            fn get_from<F: Format>(save_file: &SaveFile<F>, keys: Self::Keys<'_>) -> Self::Output {
+                ($(save_file.get_component::<$name>(keys[$idx]).ok(),)+)
+            }
+        }
+    };
+}
+
+impl_component_tuple!(1 => T0 0);
+impl_component_tuple!(2 => T0 0, T1 1);
+impl_component_tuple!(3 => T0 0, T1 1, T2 2);
+impl_component_tuple!(4 => T0 0, T1 1, T2 2, T3 3);
+impl_component_tuple!(5 => T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_component_tuple!(6 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_component_tuple!(7 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_component_tuple!(8 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_component_tuple!(9 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_component_tuple!(10 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_component_tuple!(11 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_component_tuple!(12 => T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);